@@ -0,0 +1,70 @@
+//! Times the recursive fibonacci demo program on every backend.
+//!
+//! Run with `cargo run --release --example fib_benchmark [iterations]`.
+
+use std::time::{Duration, Instant};
+
+use simple_lang::evaluator::backend_struct::Backend;
+use simple_lang::evaluator::evaluation_context::EvaluationContext;
+use simple_lang::evaluator::input_source::ScriptedInput;
+use simple_lang::evaluator::output_sink::BufferSink;
+use simple_lang::source::load_program::load_program;
+use simple_lang::type_checker::type_check_program::type_check_program;
+
+const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/simple_lang_demo_runner/demo_program/fibonacci.lang"
+);
+
+fn main() {
+    let iterations: u32 = match std::env::args().nth(1) {
+        Some(arg) => match arg.parse() {
+            Ok(iterations) if iterations > 0 => iterations,
+            _ => {
+                eprintln!("Expected a positive number of iterations, got '{}'", arg);
+                return;
+            }
+        },
+        None => 10,
+    };
+
+    let program = match load_program(PROGRAM_PATH) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("❌ Parse error: {}", e);
+            return;
+        }
+    };
+    let typed_program = match type_check_program(&program) {
+        Ok(typed_program) => typed_program,
+        Err(errors) => {
+            eprintln!("❌ Type checking failed with {} error(s)", errors.len());
+            return;
+        }
+    };
+
+    for backend in Backend::ALL {
+        let mut total = Duration::ZERO;
+        let mut output = BufferSink::default();
+        for _ in 0..iterations {
+            output.lines.clear();
+            let mut input = ScriptedInput::default();
+            let started = Instant::now();
+            let result = backend.evaluate_with_context(
+                &typed_program,
+                &mut EvaluationContext::new(&mut output, &mut input),
+            );
+            total += started.elapsed();
+            if let Err(e) = result {
+                eprintln!("❌ Runtime error on {}: {}", backend.name(), e);
+                return;
+            }
+        }
+        println!(
+            "{:<12} {:>10.2?} per run   ({})",
+            backend.name(),
+            total / iterations,
+            output.lines.join(" ")
+        );
+    }
+}
@@ -1,15 +1,32 @@
 // Enhanced main.rs for simple_lang_demo_runner with type checking support
 use simple_lang::{
-    evaluator::evaluate_program::evaluate_program, parser::parse_program::parse_program,
+    evaluator::backend_struct::Backend,
+    source::load_program::load_program,
     source::read_source_file::read_source_file,
+    type_checker::check_warnings::check_warnings,
     type_checker::type_check_program::type_check_program,
+    type_checker::warning_struct::{Severity, WarningConfig, WarningId},
 };
 use std::env;
 use std::path::PathBuf;
+use std::process;
 
 fn main() {
     println!("=== simple_lang Demo Runner with Type Checking ===");
 
+    // Select the execution backend with `--backend <name>` and the warning
+    // levels with `--allow <code>` and `--deny <code>`
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            // Exit status 2 marks invalid usage, as with most command line tools
+            process::exit(2);
+        }
+    };
+    let backend = options.backend;
+    println!("Backend: {}", backend.name());
+
     // Get current working directory
     let cwd = match env::current_dir() {
         Ok(path) => path,
@@ -25,6 +42,10 @@ fn main() {
         "demo_program/nested_function_calls.lang",
         "demo_program/control_flow.lang",
         "demo_program/if_else_statement.lang",
+        "demo_program/import_statement.lang",
+        "demo_program/void_function.lang",
+        "demo_program/string_interpolation.lang",
+        "demo_program/fibonacci.lang",
     ];
 
     for program_path in demo_programs {
@@ -64,8 +85,8 @@ fn main() {
         println!("{}", source_file.content);
         println!("{}", "-".repeat(40));
 
-        // Parse the program
-        let program = match parse_program(&source_file.content) {
+        // Parse the program and resolve its imports
+        let program = match load_program(file_path) {
             Ok(p) => {
                 println!("✅ Parsing successful");
                 p
@@ -90,22 +111,35 @@ fn main() {
         // Type check the program
         println!("\n🔍 Type checking:");
         println!("{}", "-".repeat(40));
-        match type_check_program(&program) {
-            Ok(()) => {
+        let typed_program = match type_check_program(&program) {
+            Ok(typed_program) => {
                 println!("✅ Type checking passed");
+                typed_program
             }
-            Err(e) => {
-                println!("❌ Type checking failed: {}", e);
+            Err(errors) => {
+                println!("❌ Type checking failed with {} error(s):", errors.len());
+                for error in &errors {
+                    println!("   - {}", error);
+                }
                 println!("{}", "-".repeat(40));
                 continue; // Skip execution if type checking fails
             }
+        };
+
+        let warnings = check_warnings(&program, &options.warnings);
+        for warning in &warnings {
+            println!("⚠️  {}", warning);
+        }
+        if warnings.iter().any(|w| w.severity == Severity::Error) {
+            println!("{}", "-".repeat(40));
+            continue; // Promoted warnings block execution like type errors
         }
 
         println!("\n🚀 Execution output:");
         println!("{}", "-".repeat(40));
 
         // Evaluate the program
-        match evaluate_program(&program) {
+        match backend.evaluate(&typed_program) {
             Ok(exit_code) => {
                 println!("{}", "-".repeat(40));
                 if exit_code == 0 {
@@ -127,3 +161,57 @@ fn main() {
     println!("Demo runner completed!");
     println!("{}", "=".repeat(60));
 }
+
+/// Command line options of the runner.
+#[derive(Default)]
+struct Options {
+    backend: Backend,
+    warnings: WarningConfig,
+}
+
+/// Reads the `--backend <name>`, `--allow <code>` and `--deny <code>` options,
+/// defaulting to the tree-walker and all warnings reported as warnings.
+/// A later option for the same warning code overrides an earlier one.
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--backend" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| "--backend expects a backend name".to_string())?;
+                options.backend = Backend::from_name(&name).ok_or_else(|| {
+                    let names: Vec<&str> = Backend::ALL.iter().map(|b| b.name()).collect();
+                    format!(
+                        "Unknown backend '{}', expected one of: {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--allow" | "--deny" => {
+                let code = args
+                    .next()
+                    .ok_or_else(|| format!("{} expects a warning code", arg))?;
+                let id = WarningId::from_code(&code).ok_or_else(|| {
+                    let codes: Vec<&str> = WarningId::ALL.iter().map(|id| id.code()).collect();
+                    format!(
+                        "Unknown warning '{}', expected one of: {}",
+                        code,
+                        codes.join(", ")
+                    )
+                })?;
+                let warnings = &mut options.warnings;
+                if arg == "--allow" {
+                    warnings.promoted.remove(&id);
+                    warnings.suppressed.insert(id);
+                } else {
+                    warnings.suppressed.remove(&id);
+                    warnings.promoted.insert(id);
+                }
+            }
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
@@ -0,0 +1,11 @@
+/// The function a call refers to, resolved by the type checker.
+///
+/// Builtins take precedence over user functions, and the first definition of
+/// a user function wins.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Callee {
+    /// Index into `builtins()`.
+    Builtin(usize),
+    /// Index into `TypedProgram::functions`.
+    Function(usize),
+}
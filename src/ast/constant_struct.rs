@@ -0,0 +1,17 @@
+use std::fmt;
+
+/// The compile-time value of a literal-only expression.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Constant {
+    Int(i32),
+    Str(String),
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Int(value) => write!(f, "{}", value),
+            Constant::Str(value) => write!(f, "{:?}", value),
+        }
+    }
+}
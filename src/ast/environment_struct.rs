@@ -1,30 +1,40 @@
-use std::collections::HashMap;
-use crate::ast::expression_struct::Expression;
-use crate::ast::function_struct::Function;
+use crate::ast::typed_function_struct::TypedFunction;
+use crate::ast::typed_program_struct::TypedProgram;
+use crate::evaluator::value::Value;
 
-/// Environment stores variable bindings during evaluation.
+/// Environment stores the variables of one function call during evaluation.
+///
+/// Every parameter and local declaration has a slot, its `VariableId` from
+/// the type checker, which also resolved lexical scoping, so a variable is
+/// only read through slots whose declaration is in scope. Slots are empty
+/// until their declaration runs.
 pub struct Environment<'a> {
-    pub variables: HashMap<String, Expression>,
-    pub functions: HashMap<String, &'a Function>,
+    pub slots: Vec<Option<Value>>,
+    pub program: &'a TypedProgram,
+    /// Index of the called function in the program.
+    pub function: usize,
 }
 
 impl<'a> Environment<'a> {
-    pub fn new(functions: HashMap<String, &'a Function>) -> Self {
+    /// Creates the environment for a call of the function at `function` with empty slots.
+    pub fn new(program: &'a TypedProgram, function: usize) -> Self {
         Self {
-            variables: HashMap::new(),
-            functions,
+            slots: vec![None; program.functions[function].variables.len()],
+            program,
+            function,
         }
     }
 
-    pub fn get(&self, name: &str) -> Option<&Expression> {
-        self.variables.get(name)
+    pub fn get(&self, slot: usize) -> Option<&Value> {
+        self.slots.get(slot)?.as_ref()
     }
 
-    pub fn get_function(&self, name: &str) -> Option<&'a Function> {
-        self.functions.get(name).copied()
+    pub fn get_function(&self, index: usize) -> &'a TypedFunction {
+        &self.program.functions[index]
     }
 
-    pub fn insert_variable(&mut self, name: String, value: Expression) {
-        self.variables.insert(name, value);
+    /// Stores the value of a variable in its slot.
+    pub fn set(&mut self, slot: usize, value: Value) {
+        self.slots[slot] = Some(value);
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,12 @@
 use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::string_part_struct::StringPart;
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Expression {
     IntegerLiteral(i32),
     StringLiteral(String),
+    /// A string literal containing `{expr}` holes, e.g. `"total: {count}"`.
+    InterpolatedString(Vec<StringPart>),
     VariableRef(String),
     BinaryOp {
         op: BinaryOperator,
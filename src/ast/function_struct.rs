@@ -2,9 +2,14 @@ use crate::ast::parameter_struct::Parameter;
 use crate::ast::statement_struct::Statement;
 use crate::ast::type_struct::Type;
 
+#[derive(Debug)]
 pub struct Function {
     pub name: String,
     pub params: Vec<Parameter>,
     pub return_type: Type,
     pub body: Vec<Statement>,
-}
\ No newline at end of file
+    /// Line of the function header in its source file, starting at 1.
+    pub line: usize,
+    /// Path of the file the function was loaded from, if it came from a file.
+    pub file: Option<String>,
+}
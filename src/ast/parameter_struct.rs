@@ -1,5 +1,6 @@
 use crate::ast::type_struct::Type;
 
+#[derive(Debug)]
 pub struct Parameter {
     pub name: String,
     pub param_type: Type,
@@ -1,5 +1,8 @@
 use crate::ast::function_struct::Function;
 
+#[derive(Debug)]
 pub struct Program {
     pub functions: Vec<Function>,
-}
\ No newline at end of file
+    /// Paths named by `import` statements, relative to the importing file.
+    pub imports: Vec<String>,
+}
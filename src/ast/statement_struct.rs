@@ -1,22 +1,51 @@
 use crate::ast::expression_struct::Expression;
 use crate::ast::type_struct::Type;
 
+#[derive(Debug)]
 pub enum Statement {
     VariableDeclaration {
         name: String,
         var_type: Type,
         value: Expression,
+        line: usize,
     },
     FunctionCall {
         name: String,
         args: Vec<Expression>,
+        line: usize,
     },
     If {
         condition: Expression,
         body: Vec<Statement>,
         else_body: Option<Vec<Statement>>,
+        line: usize,
     },
+    /// `return expr;`, or a bare `return;` (value `None`) in a void function.
     Return {
-        value: Expression,
+        value: Option<Expression>,
+        line: usize,
     },
 }
+
+impl Statement {
+    /// Short human-readable description of the statement for diagnostics,
+    /// e.g. `declaration of 'x'`.
+    pub fn describe(&self) -> String {
+        match self {
+            Statement::VariableDeclaration { name, .. } => format!("declaration of '{}'", name),
+            Statement::FunctionCall { name, .. } => format!("call to '{}'", name),
+            Statement::If { .. } => "if statement".to_string(),
+            Statement::Return { .. } => "return statement".to_string(),
+        }
+    }
+
+    /// Line of the statement in its source file, starting at 1.
+    pub fn line(&self) -> usize {
+        match self {
+            Statement::VariableDeclaration { line, .. }
+            | Statement::FunctionCall { line, .. }
+            | Statement::If { line, .. }
+            | Statement::Return { line, .. } => *line,
+        }
+    }
+}
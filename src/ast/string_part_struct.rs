@@ -0,0 +1,13 @@
+use crate::ast::expression_struct::Expression;
+
+/// A piece of an interpolated string literal such as `"total: {count}"`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum StringPart {
+    /// Literal text, with `{{` and `}}` already unescaped.
+    Literal(String),
+    /// A `{...}` hole, keeping its source text for error messages.
+    Hole {
+        source: String,
+        expression: Expression,
+    },
+}
@@ -0,0 +1,34 @@
+use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::callee_struct::Callee;
+use crate::ast::constant_struct::Constant;
+use crate::ast::type_struct::Type;
+use crate::ast::typed_string_part_struct::TypedStringPart;
+use crate::ast::variable_struct::VariableId;
+
+/// An expression together with the type the checker resolved for it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TypedExpression {
+    pub kind: TypedExpressionKind,
+    pub expr_type: Type,
+    /// The folded value if the expression only involves literals.
+    pub constant: Option<Constant>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TypedExpressionKind {
+    IntegerLiteral(i32),
+    StringLiteral(String),
+    InterpolatedString(Vec<TypedStringPart>),
+    /// A reference to the declaration with the given id in the enclosing function.
+    Variable(VariableId),
+    BinaryOp {
+        op: BinaryOperator,
+        left: Box<TypedExpression>,
+        right: Box<TypedExpression>,
+    },
+    FunctionCall {
+        name: String,
+        callee: Callee,
+        args: Vec<TypedExpression>,
+    },
+}
@@ -0,0 +1,16 @@
+use crate::ast::type_struct::Type;
+use crate::ast::typed_statement_struct::TypedStatement;
+use crate::ast::variable_struct::{Variable, VariableId};
+
+#[derive(Debug)]
+pub struct TypedFunction {
+    pub name: String,
+    /// Declarations of the parameters, in order.
+    pub params: Vec<VariableId>,
+    pub return_type: Type,
+    /// Every parameter and local variable declared in the function, indexed by `VariableId`.
+    pub variables: Vec<Variable>,
+    pub body: Vec<TypedStatement>,
+    /// Path of the file the function was loaded from, carried over from `Function::file`.
+    pub file: Option<String>,
+}
@@ -0,0 +1,21 @@
+use crate::ast::typed_function_struct::TypedFunction;
+
+/// A program that passed type checking, produced by `type_check_program`.
+///
+/// Every expression carries its resolved type, every variable reference
+/// points at its declaration and every call at its callee, so backends do not
+/// need to recompute any of them.
+#[derive(Debug)]
+pub struct TypedProgram {
+    pub functions: Vec<TypedFunction>,
+}
+
+impl TypedProgram {
+    /// Returns the index of the function with the given name, the first
+    /// definition if there are several.
+    pub fn find_function(&self, name: &str) -> Option<usize> {
+        self.functions
+            .iter()
+            .position(|function| function.name == name)
+    }
+}
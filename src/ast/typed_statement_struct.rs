@@ -0,0 +1,54 @@
+use crate::ast::callee_struct::Callee;
+use crate::ast::typed_expression_struct::TypedExpression;
+use crate::ast::variable_struct::{Variable, VariableId};
+
+#[derive(Debug)]
+pub enum TypedStatement {
+    VariableDeclaration {
+        variable: VariableId,
+        value: TypedExpression,
+        line: usize,
+    },
+    /// A call whose result is discarded; always a call to a void function.
+    FunctionCall {
+        name: String,
+        callee: Callee,
+        args: Vec<TypedExpression>,
+        line: usize,
+    },
+    If {
+        condition: TypedExpression,
+        body: Vec<TypedStatement>,
+        else_body: Option<Vec<TypedStatement>>,
+        line: usize,
+    },
+    Return {
+        value: Option<TypedExpression>,
+        line: usize,
+    },
+}
+
+impl TypedStatement {
+    /// Short description like `Statement::describe`, used in backtraces.
+    /// `variables` are the declarations of the enclosing function.
+    pub fn describe(&self, variables: &[Variable]) -> String {
+        match self {
+            TypedStatement::VariableDeclaration { variable, .. } => {
+                format!("declaration of '{}'", variables[variable.0].name)
+            }
+            TypedStatement::FunctionCall { name, .. } => format!("call to '{}'", name),
+            TypedStatement::If { .. } => "if statement".to_string(),
+            TypedStatement::Return { .. } => "return statement".to_string(),
+        }
+    }
+
+    /// Source line of the statement, carried over from `Statement::line`.
+    pub fn line(&self) -> usize {
+        match self {
+            TypedStatement::VariableDeclaration { line, .. }
+            | TypedStatement::FunctionCall { line, .. }
+            | TypedStatement::If { line, .. }
+            | TypedStatement::Return { line, .. } => *line,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use crate::ast::typed_expression_struct::TypedExpression;
+
+/// A piece of a type-checked interpolated string.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TypedStringPart {
+    Literal(String),
+    /// A hole, always of type string.
+    Hole(TypedExpression),
+}
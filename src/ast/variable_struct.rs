@@ -0,0 +1,15 @@
+use crate::ast::type_struct::Type;
+
+/// Identifies a variable declaration within its function.
+///
+/// Declarations are numbered in source order, starting with the parameters,
+/// so two variables with the same name in sibling blocks get different ids.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct VariableId(pub usize);
+
+/// A declared parameter or local variable.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Variable {
+    pub name: String,
+    pub var_type: Type,
+}
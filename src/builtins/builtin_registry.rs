@@ -0,0 +1,168 @@
+//! The single list of builtin functions.
+//!
+//! The type checker takes builtin signatures from here and the evaluator calls
+//! their implementations, so adding a builtin only requires a new entry in
+//! `BUILTINS` and its implementation below.
+
+use crate::ast::type_struct::Type;
+use crate::builtins::builtin_struct::Builtin;
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::value::Value;
+
+static BUILTINS: [Builtin; 4] = [
+    Builtin {
+        name: "print",
+        params: &[Type::String],
+        return_type: Type::Void,
+        implementation: builtin_print,
+    },
+    Builtin {
+        name: "int_to_string",
+        params: &[Type::I32],
+        return_type: Type::String,
+        implementation: builtin_int_to_string,
+    },
+    Builtin {
+        name: "read_line",
+        params: &[],
+        return_type: Type::String,
+        implementation: builtin_read_line,
+    },
+    Builtin {
+        name: "string_to_int",
+        params: &[Type::String],
+        return_type: Type::I32,
+        implementation: builtin_string_to_int,
+    },
+];
+
+/// Returns every builtin function.
+pub fn builtins() -> &'static [Builtin] {
+    &BUILTINS
+}
+
+/// `print(text: string)` writes the text as one line to the output sink.
+fn builtin_print(
+    args: Vec<Value>,
+    context: &mut EvaluationContext<'_>,
+) -> Result<Option<Value>, String> {
+    match args.as_slice() {
+        [Value::Str(text)] => {
+            context.output.write_line(text)?;
+            Ok(None)
+        }
+        _ => Err("print expects a single string argument".to_string()),
+    }
+}
+
+/// `int_to_string(value: i32) -> string` formats an integer in decimal.
+fn builtin_int_to_string(
+    args: Vec<Value>,
+    _context: &mut EvaluationContext<'_>,
+) -> Result<Option<Value>, String> {
+    match args.as_slice() {
+        [Value::Int(value)] => Ok(Some(Value::Str(value.to_string()))),
+        _ => Err("int_to_string expects a single i32 argument".to_string()),
+    }
+}
+
+/// `read_line() -> string` reads the next line from the input source.
+/// Reading past the end of input is a runtime error.
+fn builtin_read_line(
+    args: Vec<Value>,
+    context: &mut EvaluationContext<'_>,
+) -> Result<Option<Value>, String> {
+    if !args.is_empty() {
+        return Err("read_line expects no arguments".to_string());
+    }
+    match context.input.read_line()? {
+        Some(line) => Ok(Some(Value::Str(line))),
+        None => Err("read_line reached the end of input".to_string()),
+    }
+}
+
+/// `string_to_int(text: string) -> i32` parses a decimal integer, ignoring
+/// surrounding whitespace. Text that is not a valid i32 is a runtime error.
+fn builtin_string_to_int(
+    args: Vec<Value>,
+    _context: &mut EvaluationContext<'_>,
+) -> Result<Option<Value>, String> {
+    match args.as_slice() {
+        [Value::Str(text)] => text
+            .trim()
+            .parse::<i32>()
+            .map(|value| Some(Value::Int(value)))
+            .map_err(|_| format!("string_to_int could not parse '{}' as i32", text)),
+        _ => Err("string_to_int expects a single string argument".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::input_source::ScriptedInput;
+    use crate::evaluator::output_sink::BufferSink;
+
+    /// Looks up a builtin function by name.
+    fn find_builtin(name: &str) -> Option<&'static Builtin> {
+        builtins().iter().find(|builtin| builtin.name == name)
+    }
+
+    #[test]
+    fn test_find_builtin() {
+        let builtin = find_builtin("int_to_string").unwrap();
+        assert_eq!(builtin.params, &[Type::I32]);
+        assert_eq!(builtin.return_type, Type::String);
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context = EvaluationContext::new(&mut output, &mut input);
+        assert_eq!(
+            (builtin.implementation)(vec![Value::Int(-7)], &mut context),
+            Ok(Some(Value::Str("-7".to_string())))
+        );
+        assert!(find_builtin("println").is_none());
+    }
+
+    #[test]
+    fn test_builtin_argument_errors() {
+        let print = find_builtin("print").unwrap();
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context = EvaluationContext::new(&mut output, &mut input);
+        assert_eq!(
+            (print.implementation)(vec![Value::Int(1)], &mut context),
+            Err("print expects a single string argument".to_string())
+        );
+        assert_eq!(
+            (print.implementation)(vec![Value::Str("ok".to_string())], &mut context),
+            Ok(None)
+        );
+        assert_eq!(output.lines, vec!["ok"]);
+    }
+
+    #[test]
+    fn test_input_builtins() {
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::new(["  42 ", "forty-two"]);
+        let mut context = EvaluationContext::new(&mut output, &mut input);
+        let read_line = find_builtin("read_line").unwrap().implementation;
+        let string_to_int = find_builtin("string_to_int").unwrap().implementation;
+
+        let line = read_line(vec![], &mut context).unwrap().unwrap();
+        assert_eq!(line, Value::Str("  42 ".to_string()));
+        assert_eq!(
+            string_to_int(vec![line], &mut context),
+            Ok(Some(Value::Int(42)))
+        );
+
+        let line = read_line(vec![], &mut context).unwrap().unwrap();
+        assert_eq!(
+            string_to_int(vec![line], &mut context),
+            Err("string_to_int could not parse 'forty-two' as i32".to_string())
+        );
+        assert_eq!(
+            read_line(vec![], &mut context),
+            Err("read_line reached the end of input".to_string())
+        );
+    }
+}
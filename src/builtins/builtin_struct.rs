@@ -0,0 +1,17 @@
+use crate::ast::type_struct::Type;
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::value::Value;
+
+/// Implementation of a builtin, called with already evaluated arguments and
+/// the evaluation context for side effects such as output.
+/// Returns `None` for a builtin with a void return type.
+pub type BuiltinFn = fn(Vec<Value>, &mut EvaluationContext<'_>) -> Result<Option<Value>, String>;
+
+/// A function provided by the language rather than defined in source code.
+#[derive(Debug)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub params: &'static [Type],
+    pub return_type: Type,
+    pub implementation: BuiltinFn,
+}
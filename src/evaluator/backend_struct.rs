@@ -0,0 +1,183 @@
+use crate::ast::typed_program_struct::TypedProgram;
+use crate::evaluator::evaluate_program::{evaluate_program, evaluate_program_with_context};
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::runtime_error::RuntimeError;
+use crate::vm::compile_program::compile_program;
+use crate::vm::run_bytecode::{run_bytecode, run_bytecode_with_context};
+
+/// Selects how a type-checked program is executed.
+///
+/// Both backends produce the same output, exit code and runtime errors;
+/// they differ in how many steps a program takes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Backend {
+    /// Walks the AST directly.
+    #[default]
+    TreeWalker,
+    /// Compiles the typed AST to bytecode and runs it on the stack VM.
+    Bytecode,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::TreeWalker, Backend::Bytecode];
+
+    /// The name used to select the backend, e.g. on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::TreeWalker => "tree-walker",
+            Backend::Bytecode => "bytecode",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL
+            .into_iter()
+            .find(|backend| backend.name() == name)
+    }
+
+    /// Runs the type-checked program on this backend with stdout and stdin,
+    /// like `evaluate_program`.
+    pub fn evaluate(self, program: &TypedProgram) -> Result<i32, RuntimeError> {
+        match self {
+            Backend::TreeWalker => evaluate_program(program),
+            Backend::Bytecode => run_bytecode(&compile_program(program)?),
+        }
+    }
+
+    /// Runs the type-checked program on this backend using the given context.
+    pub fn evaluate_with_context(
+        self,
+        program: &TypedProgram,
+        context: &mut EvaluationContext<'_>,
+    ) -> Result<i32, RuntimeError> {
+        match self {
+            Backend::TreeWalker => evaluate_program_with_context(program, context),
+            Backend::Bytecode => run_bytecode_with_context(&compile_program(program)?, context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::input_source::ScriptedInput;
+    use crate::evaluator::output_sink::BufferSink;
+    use crate::evaluator::runtime_error::RuntimeErrorKind;
+    use crate::source::load_program::load_program;
+    use crate::type_checker::check_warnings::check_warnings;
+    use crate::type_checker::type_check_program::type_check_program;
+    use crate::type_checker::warning_struct::WarningConfig;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_backend_names_round_trip() {
+        for backend in Backend::ALL {
+            assert_eq!(Backend::from_name(backend.name()), Some(backend));
+        }
+        assert_eq!(Backend::from_name("jit"), None);
+    }
+
+    /// Call depth limit for the failing corpus, low enough that the stack
+    /// overflow program fails after only a few calls.
+    const FAILING_MAX_CALL_DEPTH: usize = 50;
+    /// Step budget for the failing corpus.
+    const FAILING_FUEL: u64 = 100_000;
+
+    /// Lists the `.lang` files directly inside `dir` of the demo runner, sorted by name.
+    fn corpus(dir: &str) -> Vec<PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("simple_lang_demo_runner")
+            .join(dir);
+        let mut paths: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "lang")
+            })
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty(), "{}", dir.display());
+        paths
+    }
+
+    /// Runs the program at `path` on every backend, returning the full result
+    /// and the captured output of each, and asserts that they all agree.
+    /// The program must be free of warnings.
+    fn run_on_all_backends(
+        path: &Path,
+        configure: impl Fn(EvaluationContext<'_>) -> EvaluationContext<'_>,
+    ) -> (Result<i32, RuntimeError>, String) {
+        let program = load_program(path.to_str().unwrap())
+            .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+        let typed = type_check_program(&program)
+            .unwrap_or_else(|errors| panic!("{}: {:?}", path.display(), errors));
+        let warnings = check_warnings(&program, &WarningConfig::default());
+        assert!(warnings.is_empty(), "{}: {:?}", path.display(), warnings);
+
+        let mut results: Vec<_> = Backend::ALL
+            .into_iter()
+            .map(|backend| {
+                let mut output = BufferSink::default();
+                let mut input = ScriptedInput::default();
+                let result = backend.evaluate_with_context(
+                    &typed,
+                    &mut configure(EvaluationContext::new(&mut output, &mut input)),
+                );
+                (result, output.contents())
+            })
+            .collect();
+
+        for (backend, result) in Backend::ALL.iter().zip(&results).skip(1) {
+            assert_eq!(
+                result,
+                &results[0],
+                "{}: {} disagrees with {}",
+                path.display(),
+                backend.name(),
+                Backend::ALL[0].name()
+            );
+        }
+        results.swap_remove(0)
+    }
+
+    /// Runs every demo program on both backends and compares the results.
+    #[test]
+    fn test_backends_agree_on_demo_programs() {
+        for path in corpus("demo_program") {
+            let (result, _) = run_on_all_backends(&path, |context| context);
+            assert!(result.is_ok(), "{}: {:?}", path.display(), result);
+        }
+    }
+
+    /// Runs every failing program on both backends and compares the errors,
+    /// including their backtraces.
+    #[test]
+    fn test_backends_agree_on_failing_programs() {
+        for path in corpus("demo_program/failing") {
+            let (result, _) = run_on_all_backends(&path, |context| {
+                context
+                    .with_max_call_depth(FAILING_MAX_CALL_DEPTH)
+                    .with_fuel(FAILING_FUEL)
+            });
+            let error = result.expect_err(&path.display().to_string());
+
+            let expected = match path.file_stem().unwrap().to_str().unwrap() {
+                "division_by_zero" => RuntimeErrorKind::DivisionByZero,
+                "exhausted_fuel" => RuntimeErrorKind::FuelExhausted {
+                    steps: FAILING_FUEL,
+                },
+                "stack_overflow" => RuntimeErrorKind::StackOverflow {
+                    function: "dive".to_string(),
+                    max_depth: FAILING_MAX_CALL_DEPTH,
+                },
+                other => panic!("No expected error for '{}'", other),
+            };
+            assert_eq!(error.kind, expected, "{}", path.display());
+            assert!(!error.backtrace.is_empty(), "{}", path.display());
+            for frame in &error.backtrace {
+                assert_eq!(frame.file.as_deref(), path.to_str(), "{}", error);
+            }
+        }
+    }
+}
@@ -1,355 +1,746 @@
 // Enhanced evaluate_function.rs with proper if-else statement evaluation
 
 use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::callee_struct::Callee;
 use crate::ast::environment_struct::Environment;
-use crate::ast::expression_struct::Expression;
-use crate::ast::function_struct::Function;
-use crate::ast::statement_struct::Statement;
-
-/// Evaluates a function given the function definition and argument expressions.
-/// Returns the resulting Expression or an error string.
+use crate::ast::type_struct::Type;
+use crate::ast::typed_expression_struct::{TypedExpression, TypedExpressionKind};
+use crate::ast::typed_program_struct::TypedProgram;
+use crate::ast::typed_statement_struct::TypedStatement;
+use crate::ast::typed_string_part_struct::TypedStringPart;
+use crate::ast::variable_struct::VariableId;
+use crate::builtins::builtin_registry::builtins;
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::runtime_error::{RuntimeError, RuntimeErrorKind};
+use crate::evaluator::value::Value;
+
+/// Evaluates the function at index `function` of the typed program given the argument values.
+/// Returns the resulting Value, or `None` for a void function, or a runtime error.
 /// Enhanced with proper if-else statement evaluation.
-pub fn evaluate_function<'a>(
-    function: &'a Function,
-    args: Vec<Expression>,
-    outer_env: &Environment<'a>,
-) -> Result<Expression, String> {
-    if args.len() != function.params.len() {
-        return Err(format!(
-            "Expected {} arguments but got {}",
-            function.params.len(),
-            args.len()
-        ));
+///
+/// Active calls and the pending work of each are kept on explicit stacks
+/// instead of the host stack, so any thread can evaluate a program as deep as
+/// the context's `max_call_depth` allows; exceeding it is a
+/// `RuntimeErrorKind::StackOverflow`. Self tail calls (`return f(...)` inside
+/// `f`) reuse the current frame, so they do not count towards the limit.
+/// An error gets a frame in its backtrace for every call it propagated out of.
+pub fn evaluate_function(
+    function: usize,
+    args: Vec<Value>,
+    program: &TypedProgram,
+    context: &mut EvaluationContext<'_>,
+) -> Result<Option<Value>, RuntimeError> {
+    let call_depth = context.call_depth;
+    let mut evaluation = Evaluation {
+        program,
+        frames: Vec::new(),
+        tasks: Vec::new(),
+        values: Vec::new(),
+        returned: None,
+    };
+
+    match evaluation.run(function, args, context) {
+        Ok(()) => Ok(evaluation.returned),
+        Err(error) => {
+            context.call_depth = call_depth;
+            Err(evaluation.unwind(error))
+        }
     }
+}
 
-    // Create new environment inheriting the functions from the outer environment
-    let mut env = Environment::new(outer_env.functions.clone());
+/// The state of one evaluation: the active calls, the work left to do in
+/// them, and the values computed so far.
+struct Evaluation<'p> {
+    program: &'p TypedProgram,
+    /// Active calls, innermost last.
+    frames: Vec<CallFrame<'p>>,
+    /// Work left to do, next task last.
+    tasks: Vec<Task<'p>>,
+    /// Values of evaluated expressions not consumed yet.
+    values: Vec<Value>,
+    /// Result of the call that returned most recently.
+    returned: Option<Value>,
+}
 
-    // Bind parameters to arguments
-    for (param, arg) in function.params.iter().zip(args.into_iter()) {
-        env.insert_variable(param.name.clone(), arg);
-    }
+/// One active call of a user function.
+struct CallFrame<'p> {
+    env: Environment<'p>,
+    /// The innermost statement started by the call, for backtraces.
+    statement: Option<&'p TypedStatement>,
+    /// Sizes of the task and value stacks when the call started; everything
+    /// above them belongs to the call.
+    tasks_base: usize,
+    values_base: usize,
+}
 
-    evaluate_statements(&function.body, &mut env)
+/// A piece of work of the current call.
+enum Task<'p> {
+    /// Runs the statements of a block, starting with the one at `next`.
+    Block {
+        statements: &'p [TypedStatement],
+        next: usize,
+    },
+    /// Evaluates an expression and pushes its value.
+    Evaluate(&'p TypedExpression),
+    /// Pops a value into the slot of a variable.
+    Store(VariableId),
+    /// Pops a condition and runs one of the branches of an `if`.
+    Branch {
+        body: &'p [TypedStatement],
+        else_body: Option<&'p [TypedStatement]>,
+    },
+    /// Pops two operands and pushes the result of the operation.
+    BinaryOp(&'p BinaryOperator),
+    /// Pushes the literal part of an interpolated string.
+    Literal(&'p str),
+    /// Pops the given number of strings and pushes them joined.
+    Concat(usize),
+    /// Pops the given number of arguments and calls the function. The result
+    /// of a statement-level call is discarded.
+    Call {
+        name: &'p str,
+        callee: Callee,
+        args: usize,
+        discard: bool,
+    },
+    /// Takes the result of the user function call that just returned.
+    Returned { name: &'p str, discard: bool },
+    /// Pops a value and returns it from the current call.
+    Return,
+    /// Pops the given number of arguments and restarts the current call with them.
+    TailCall(usize),
+    /// Reached when the body of the current call ends without a `return`.
+    EndOfBody,
 }
 
-/// Evaluate a list of statements in order, returning the final expression if a return is encountered.
-/// Enhanced to handle if-else statements properly.
-fn evaluate_statements<'a>(
-    statements: &[Statement],
-    env: &mut Environment<'a>,
-) -> Result<Expression, String> {
-    for stmt in statements {
-        match evaluate_statement(stmt, env)? {
-            Some(ret_val) => return Ok(ret_val), // Return statement encountered
-            None => continue,
+impl<'p> Evaluation<'p> {
+    /// Calls the function and runs until it returns.
+    fn run(
+        &mut self,
+        function: usize,
+        args: Vec<Value>,
+        context: &mut EvaluationContext<'_>,
+    ) -> Result<(), RuntimeError> {
+        self.call(function, args, context)?;
+        while let Some(task) = self.tasks.pop() {
+            self.execute(task, context)?;
         }
+        Ok(())
     }
-    Err("Function did not return a value".to_string())
-}
 
-/// Evaluate a single statement.
-/// Enhanced with proper if-else statement handling.
-fn evaluate_statement<'a>(
-    stmt: &Statement,
-    env: &mut Environment<'a>,
-) -> Result<Option<Expression>, String> {
-    match stmt {
-        Statement::VariableDeclaration { name, value, .. } => {
-            let val = evaluate_expression(value, env)?;
-            env.insert_variable(name.clone(), val);
-            Ok(None)
+    fn frame(&self) -> &CallFrame<'p> {
+        self.frames.last().expect("a call is active")
+    }
+
+    fn frame_mut(&mut self) -> &mut CallFrame<'p> {
+        self.frames.last_mut().expect("a call is active")
+    }
+
+    /// Starts a call of a user function with a new frame; the parameters
+    /// occupy the slots of their declarations.
+    fn call(
+        &mut self,
+        function: usize,
+        args: Vec<Value>,
+        context: &mut EvaluationContext<'_>,
+    ) -> Result<(), RuntimeError> {
+        let definition = &self.program.functions[function];
+        if args.len() != definition.params.len() {
+            return Err(format!(
+                "Expected {} arguments but got {}",
+                definition.params.len(),
+                args.len()
+            )
+            .into());
+        }
+
+        if context.call_depth >= context.max_call_depth {
+            return Err(RuntimeErrorKind::StackOverflow {
+                function: definition.name.clone(),
+                max_depth: context.max_call_depth,
+            }
+            .into());
+        }
+        context.call_depth += 1;
+
+        let mut env = Environment::new(self.program, function);
+        for (param, arg) in definition.params.iter().zip(args) {
+            env.set(param.0, arg);
         }
+        self.frames.push(CallFrame {
+            env,
+            statement: None,
+            tasks_base: self.tasks.len(),
+            values_base: self.values.len(),
+        });
+        self.start_body();
+        Ok(())
+    }
+
+    /// Schedules the body of the current call.
+    fn start_body(&mut self) {
+        let definition = self.frame().env.get_function(self.frame().env.function);
+        self.tasks.push(Task::EndOfBody);
+        self.tasks.push(Task::Block {
+            statements: &definition.body,
+            next: 0,
+        });
+    }
+
+    /// Ends the current call, dropping its remaining work.
+    fn return_from_call(&mut self, value: Option<Value>, context: &mut EvaluationContext<'_>) {
+        let frame = self.frames.pop().expect("a call is active");
+        self.tasks.truncate(frame.tasks_base);
+        self.values.truncate(frame.values_base);
+        context.call_depth -= 1;
+        self.returned = value;
+    }
+
+    fn pop_value(&mut self) -> Value {
+        self.values.pop().expect("an operand was evaluated")
+    }
 
-        Statement::FunctionCall { name, args } => match name.as_str() {
-            "print" => {
-                if args.len() != 1 {
-                    return Err("print expects exactly one argument".to_string());
+    fn pop_values(&mut self, count: usize) -> Vec<Value> {
+        self.values.split_off(self.values.len() - count)
+    }
+
+    /// Schedules the evaluation of expressions so their values end up on the
+    /// value stack in order.
+    fn evaluate_all(&mut self, expressions: &'p [TypedExpression]) {
+        self.tasks
+            .extend(expressions.iter().rev().map(Task::Evaluate));
+    }
+
+    fn execute(
+        &mut self,
+        task: Task<'p>,
+        context: &mut EvaluationContext<'_>,
+    ) -> Result<(), RuntimeError> {
+        match task {
+            Task::Block { statements, next } => {
+                if let Some(stmt) = statements.get(next) {
+                    self.tasks.push(Task::Block {
+                        statements,
+                        next: next + 1,
+                    });
+                    // Record the innermost statement as the error location
+                    self.frame_mut().statement = Some(stmt);
+                    context.step()?;
+                    self.start_statement(stmt, context);
                 }
-                let value = evaluate_expression(&args[0], env)?;
-                match value {
-                    Expression::StringLiteral(s) => {
-                        println!("{}", s);
-                        Ok(None)
-                    }
+            }
 
-                    _ => Err("print only supports strings".to_string()),
+            Task::Evaluate(expr) => {
+                context.step()?;
+                self.start_expression(expr)?;
+            }
+
+            Task::Store(variable) => {
+                let value = self.pop_value();
+                self.frame_mut().env.set(variable.0, value);
+            }
+
+            Task::Branch { body, else_body } => {
+                let condition = self.pop_value();
+                let branch = if is_truthy(&condition)? {
+                    Some(body)
+                } else {
+                    else_body
+                };
+                // Blocks need no scope of their own at runtime: the type checker
+                // already gave every declaration its own slot
+                if let Some(statements) = branch {
+                    self.tasks.push(Task::Block {
+                        statements,
+                        next: 0,
+                    });
                 }
             }
 
-            _ => {
-                let evaluated_args = evaluate_arguments(args, env)?;
-                let result = evaluate_function_by_name(name, evaluated_args, env)?;
-                Ok(Some(result))
+            Task::BinaryOp(op) => {
+                let right = self.pop_value();
+                let left = self.pop_value();
+                self.values.push(evaluate_binary_op(op, &left, &right)?);
             }
-        },
 
-        Statement::If {
-            condition,
-            body,
-            else_body,
-        } => {
-            let cond_val = evaluate_expression(condition, env)?;
-            if is_truthy(&cond_val)? {
-                // Execute if body statements
-                for stmt in body {
-                    if let Some(ret_val) = evaluate_statement(stmt, env)? {
-                        return Ok(Some(ret_val)); // Early return from if block
+            Task::Literal(text) => self.values.push(Value::Str(text.to_string())),
+
+            Task::Concat(count) => {
+                let mut text = String::new();
+                for part in self.pop_values(count) {
+                    match part {
+                        Value::Str(s) => text.push_str(&s),
+                        _ => {
+                            return Err("Interpolation hole did not evaluate to a string"
+                                .to_string()
+                                .into());
+                        }
                     }
                 }
-            } else if let Some(else_statements) = else_body {
-                // Execute else body statements
-                for stmt in else_statements {
-                    if let Some(ret_val) = evaluate_statement(stmt, env)? {
-                        return Ok(Some(ret_val)); // Early return from else block
+                self.values.push(Value::Str(text));
+            }
+
+            Task::Call {
+                name,
+                callee,
+                args,
+                discard,
+            } => {
+                let args = self.pop_values(args);
+                match callee {
+                    Callee::Builtin(index) => {
+                        let builtin = &builtins()[index];
+                        if args.len() != builtin.params.len() {
+                            return Err(format!(
+                                "{} expects {} argument(s) but got {}",
+                                name,
+                                builtin.params.len(),
+                                args.len()
+                            )
+                            .into());
+                        }
+                        let result = (builtin.implementation)(args, context)?;
+                        self.take_result(name, result, discard)?;
+                    }
+                    Callee::Function(index) => {
+                        self.tasks.push(Task::Returned { name, discard });
+                        self.call(index, args, context)?;
                     }
                 }
             }
-            Ok(None)
-        }
 
-        Statement::Return { value } => {
-            let val = evaluate_expression(value, env)?;
-            Ok(Some(val))
+            Task::Returned { name, discard } => {
+                let result = self.returned.take();
+                self.take_result(name, result, discard)?;
+            }
+
+            Task::Return => {
+                let value = self.pop_value();
+                self.return_from_call(Some(value), context);
+            }
+
+            Task::TailCall(args) => {
+                // Start over in the same frame with the arguments of the tail call
+                let args = self.pop_values(args);
+                let frame = self.frame_mut();
+                let definition = frame.env.get_function(frame.env.function);
+                frame.statement = None;
+                if args.len() != definition.params.len() {
+                    return Err(format!(
+                        "Expected {} arguments but got {}",
+                        definition.params.len(),
+                        args.len()
+                    )
+                    .into());
+                }
+
+                frame.env.slots.fill(None);
+                for (param, arg) in definition.params.iter().zip(args) {
+                    frame.env.set(param.0, arg);
+                }
+                let (tasks_base, values_base) = (frame.tasks_base, frame.values_base);
+                self.tasks.truncate(tasks_base);
+                self.values.truncate(values_base);
+                self.start_body();
+            }
+
+            Task::EndOfBody => {
+                let frame = self.frame_mut();
+                let definition = frame.env.get_function(frame.env.function);
+                frame.statement = None;
+                // Void functions may return implicitly by reaching the end of their body
+                if definition.return_type != Type::Void {
+                    return Err(
+                        format!("Function '{}' did not return a value", definition.name).into(),
+                    );
+                }
+                self.return_from_call(None, context);
+            }
         }
+        Ok(())
     }
-}
 
-/// Evaluate an expression in the given environment.
-/// Enhanced with better error handling and support for all binary operations.
-pub fn evaluate_expression<'a>(
-    expr: &Expression,
-    env: &Environment<'a>,
-) -> Result<Expression, String> {
-    match expr {
-        Expression::IntegerLiteral(_) | Expression::StringLiteral(_) => Ok(expr.clone()),
-
-        Expression::VariableRef(name) => env
-            .get(name)
-            .cloned()
-            .ok_or_else(|| format!("Variable '{}' not found", name)),
-
-        Expression::BinaryOp { op, left, right } => {
-            let l_val = evaluate_expression(left, env)?;
-            let r_val = evaluate_expression(right, env)?;
-            evaluate_binary_op(op, &l_val, &r_val)
+    /// Schedules the work of a statement.
+    fn start_statement(&mut self, stmt: &'p TypedStatement, context: &mut EvaluationContext<'_>) {
+        match stmt {
+            TypedStatement::VariableDeclaration {
+                variable, value, ..
+            } => {
+                self.tasks.push(Task::Store(*variable));
+                self.tasks.push(Task::Evaluate(value));
+            }
+
+            TypedStatement::FunctionCall {
+                name, callee, args, ..
+            } => {
+                // A call used as a statement only runs for its side effects;
+                // its result is discarded rather than returned from the caller.
+                self.tasks.push(Task::Call {
+                    name,
+                    callee: *callee,
+                    args: args.len(),
+                    discard: true,
+                });
+                self.evaluate_all(args);
+            }
+
+            TypedStatement::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                self.tasks.push(Task::Branch {
+                    body,
+                    else_body: else_body.as_deref(),
+                });
+                self.tasks.push(Task::Evaluate(condition));
+            }
+
+            // `return f(...)` inside `f` itself reuses the current frame
+            TypedStatement::Return {
+                value:
+                    Some(TypedExpression {
+                        kind: TypedExpressionKind::FunctionCall { callee, args, .. },
+                        ..
+                    }),
+                ..
+            } if *callee == Callee::Function(self.frame().env.function) => {
+                self.tasks.push(Task::TailCall(args.len()));
+                self.evaluate_all(args);
+            }
+
+            TypedStatement::Return {
+                value: Some(value), ..
+            } => {
+                self.tasks.push(Task::Return);
+                self.tasks.push(Task::Evaluate(value));
+            }
+
+            TypedStatement::Return { value: None, .. } => self.return_from_call(None, context),
         }
+    }
 
-        Expression::FunctionCall { name, args } => {
-            // Handle built-in functions
-            match name.as_str() {
-                "int_to_string" => {
-                    if args.len() != 1 {
-                        return Err("int_to_string expects exactly one argument".to_string());
-                    }
-                    let val = evaluate_expression(&args[0], env)?;
-                    match val {
-                        Expression::IntegerLiteral(i) => {
-                            Ok(Expression::StringLiteral(i.to_string()))
-                        }
-                        _ => Err("int_to_string expects an integer argument".to_string()),
-                    }
-                }
-                _ => {
-                    let evaluated_args = evaluate_arguments(args, env)?;
-                    evaluate_function_by_name(name, evaluated_args, env)
+    /// Evaluates a leaf expression, or schedules the work of a compound one.
+    fn start_expression(&mut self, expr: &'p TypedExpression) -> Result<(), RuntimeError> {
+        match &expr.kind {
+            TypedExpressionKind::IntegerLiteral(value) => self.values.push(Value::Int(*value)),
+            TypedExpressionKind::StringLiteral(text) => self.values.push(Value::Str(text.clone())),
+
+            TypedExpressionKind::InterpolatedString(parts) => {
+                self.tasks.push(Task::Concat(parts.len()));
+                for part in parts.iter().rev() {
+                    self.tasks.push(match part {
+                        TypedStringPart::Literal(literal) => Task::Literal(literal),
+                        TypedStringPart::Hole(expression) => Task::Evaluate(expression),
+                    });
                 }
             }
+
+            TypedExpressionKind::Variable(variable) => {
+                let env = &self.frame().env;
+                let value = env.get(variable.0).cloned().ok_or_else(|| {
+                    format!(
+                        "Variable '{}' read before its declaration",
+                        env.get_function(env.function).variables[variable.0].name
+                    )
+                })?;
+                self.values.push(value);
+            }
+
+            TypedExpressionKind::BinaryOp { op, left, right } => {
+                self.tasks.push(Task::BinaryOp(op));
+                self.tasks.push(Task::Evaluate(right));
+                self.tasks.push(Task::Evaluate(left));
+            }
+
+            TypedExpressionKind::FunctionCall { name, callee, args } => {
+                self.tasks.push(Task::Call {
+                    name,
+                    callee: *callee,
+                    args: args.len(),
+                    discard: false,
+                });
+                self.evaluate_all(args);
+            }
         }
+        Ok(())
     }
-}
 
-/// Evaluates all argument expressions in order.
-fn evaluate_arguments<'a>(
-    args: &[Expression],
-    env: &Environment<'a>,
-) -> Result<Vec<Expression>, String> {
-    args.iter()
-        .map(|arg| evaluate_expression(arg, env))
-        .collect()
-}
+    /// Pushes the result of a call used as a value, or drops it for a statement-level call.
+    fn take_result(
+        &mut self,
+        name: &str,
+        result: Option<Value>,
+        discard: bool,
+    ) -> Result<(), RuntimeError> {
+        match result {
+            _ if discard => {}
+            Some(value) => self.values.push(value),
+            None => return Err(format!("Function '{}' did not return a value", name).into()),
+        }
+        Ok(())
+    }
 
-/// Looks up a function by name and evaluates it with args.
-fn evaluate_function_by_name<'a>(
-    name: &str,
-    args: Vec<Expression>,
-    env: &Environment<'a>,
-) -> Result<Expression, String> {
-    let func = env
-        .get_function(name)
-        .ok_or_else(|| format!("Function '{}' not found", name))?;
-
-    evaluate_function(func, args, env)
+    /// Adds a backtrace frame to the error for every active call, innermost first.
+    fn unwind(&mut self, mut error: RuntimeError) -> RuntimeError {
+        while let Some(frame) = self.frames.pop() {
+            let definition = frame.env.get_function(frame.env.function);
+            if let Some(stmt) = frame.statement {
+                error = error.at_statement(stmt.line(), || stmt.describe(&definition.variables));
+            }
+            // Parameters cannot be reassigned, so they still hold the arguments
+            let args = definition
+                .params
+                .iter()
+                .filter_map(|param| {
+                    let value = frame.env.get(param.0)?.clone();
+                    Some((definition.variables[param.0].name.clone(), value))
+                })
+                .collect();
+            error = error.in_frame(&definition.name, definition.file.as_deref(), args);
+        }
+        error
+    }
 }
 
 /// Helper for truthiness of condition expressions.
 /// In simple_lang, only i32 values are considered for truthiness:
 /// - 0 is false
 /// - Any non-zero value is true
-fn is_truthy(expr: &Expression) -> Result<bool, String> {
-    match expr {
-        Expression::IntegerLiteral(i) => Ok(*i != 0),
+pub fn is_truthy(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Int(i) => Ok(*i != 0),
         _ => Err("Invalid type for condition expression; expected i32".to_string()),
     }
 }
 
 /// Enhanced binary operation evaluation with proper overflow checking.
-fn evaluate_binary_op(
+pub fn evaluate_binary_op(
     op: &BinaryOperator,
-    left: &Expression,
-    right: &Expression,
-) -> Result<Expression, String> {
+    left: &Value,
+    right: &Value,
+) -> Result<Value, RuntimeErrorKind> {
     use BinaryOperator::*;
-    use Expression::IntegerLiteral;
+    use Value::Int;
+
+    let overflow = |operation: &str| RuntimeErrorKind::IntegerOverflow {
+        operation: operation.to_string(),
+    };
 
     match (left, right) {
-        (IntegerLiteral(l), IntegerLiteral(r)) => {
+        (Int(l), Int(r)) => {
             let result = match op {
-                Add => l.checked_add(*r).ok_or("Integer overflow on addition")?,
-                Subtract => l.checked_sub(*r).ok_or("Integer overflow on subtraction")?,
+                Add => l.checked_add(*r).ok_or_else(|| overflow("addition"))?,
+                Subtract => l.checked_sub(*r).ok_or_else(|| overflow("subtraction"))?,
                 Multiply => l
                     .checked_mul(*r)
-                    .ok_or("Integer overflow on multiplication")?,
+                    .ok_or_else(|| overflow("multiplication"))?,
                 Divide => {
                     if *r == 0 {
-                        return Err("Division by zero".to_string());
+                        return Err(RuntimeErrorKind::DivisionByZero);
                     }
-                    l.checked_div(*r).ok_or("Integer overflow on division")?
+                    l.checked_div(*r).ok_or_else(|| overflow("division"))?
                 }
-                GreaterThan => return Ok(IntegerLiteral(if l > r { 1 } else { 0 })),
-                LessThan => return Ok(IntegerLiteral(if l < r { 1 } else { 0 })),
-                Equal => return Ok(IntegerLiteral(if l == r { 1 } else { 0 })),
+                GreaterThan => return Ok(Int(if l > r { 1 } else { 0 })),
+                LessThan => return Ok(Int(if l < r { 1 } else { 0 })),
+                Equal => return Ok(Int(if l == r { 1 } else { 0 })),
             };
-            Ok(IntegerLiteral(result))
+            Ok(Int(result))
         }
         // Support string equality comparison
-        (Expression::StringLiteral(l), Expression::StringLiteral(r)) => match op {
-            Equal => Ok(IntegerLiteral(if l == r { 1 } else { 0 })),
-            _ => Err("Only equality comparison is supported for strings".to_string()),
+        (Value::Str(l), Value::Str(r)) => match op {
+            Equal => Ok(Int(if l == r { 1 } else { 0 })),
+            _ => Err(RuntimeErrorKind::Other(
+                "Only equality comparison is supported for strings".to_string(),
+            )),
         },
-        _ => Err("Binary operations require compatible types".to_string()),
+        _ => Err(RuntimeErrorKind::Other(
+            "Binary operations require compatible types".to_string(),
+        )),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::type_struct::Type;
-    use std::collections::HashMap;
+    use crate::evaluator::input_source::ScriptedInput;
+    use crate::evaluator::output_sink::BufferSink;
+    use crate::parser::parse_program::parse_program;
+    use crate::type_checker::type_check_program::type_check_program;
+
+    /// Runs `f` with a context that collects output and has no input.
+    fn with_context<T>(f: impl FnOnce(&mut EvaluationContext<'_>) -> T) -> T {
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        f(&mut EvaluationContext::new(&mut output, &mut input))
+    }
+
+    fn check(source: &str) -> TypedProgram {
+        type_check_program(&parse_program(source).unwrap()).unwrap()
+    }
+
+    /// Type-checks a function `f(x: i32) -> i32` with the given body, followed by a `main`.
+    fn check_with_x(body: &str) -> TypedProgram {
+        check(&format!(
+            "f: function(x: i32) -> i32 {{\n{}\n}};\n\nmain: function() -> i32 {{\n    return f(1);\n}};\n",
+            body
+        ))
+    }
+
+    /// Calls `f` of the program with the given value of `x`.
+    fn call_with_x(program: &TypedProgram, x: i32) -> Result<Option<Value>, RuntimeError> {
+        with_context(|context| evaluate_function(0, vec![Value::Int(x)], program, context))
+    }
 
     #[test]
     fn test_evaluate_if_statement_true() {
-        let mut env = Environment::new(HashMap::new());
-        env.insert_variable("x".to_string(), Expression::IntegerLiteral(5));
-
-        let if_stmt = Statement::If {
-            condition: Expression::BinaryOp {
-                op: BinaryOperator::GreaterThan,
-                left: Box::new(Expression::VariableRef("x".to_string())),
-                right: Box::new(Expression::IntegerLiteral(0)),
-            },
-            body: vec![Statement::VariableDeclaration {
-                name: "result".to_string(),
-                var_type: Type::I32,
-                value: Expression::IntegerLiteral(42),
-            }],
-            else_body: None,
-        };
-
-        let result = evaluate_statement(&if_stmt, &mut env);
-        assert!(result.is_ok());
-        assert!(env.get("result").is_some());
+        let program = check_with_x("    if (x > 0) {\n        return 42;\n    };\n    return 0;");
+        assert_eq!(call_with_x(&program, 5), Ok(Some(Value::Int(42))));
     }
 
     #[test]
     fn test_evaluate_if_statement_false() {
-        let mut env = Environment::new(HashMap::new());
-        env.insert_variable("x".to_string(), Expression::IntegerLiteral(-5));
-
-        let if_stmt = Statement::If {
-            condition: Expression::BinaryOp {
-                op: BinaryOperator::GreaterThan,
-                left: Box::new(Expression::VariableRef("x".to_string())),
-                right: Box::new(Expression::IntegerLiteral(0)),
-            },
-            body: vec![Statement::VariableDeclaration {
-                name: "result".to_string(),
-                var_type: Type::I32,
-                value: Expression::IntegerLiteral(42),
-            }],
-            else_body: None,
-        };
-
-        let result = evaluate_statement(&if_stmt, &mut env);
-        assert!(result.is_ok());
-        assert!(env.get("result").is_none()); // Should not be executed
+        let program = check_with_x(
+            "    if (x > 0) {\n        result: i32 = 42;\n        return result;\n    };\n    return x;",
+        );
+        // The body is not executed
+        assert_eq!(call_with_x(&program, -5), Ok(Some(Value::Int(-5))));
     }
 
     #[test]
     fn test_evaluate_if_else_statement() {
-        let mut env = Environment::new(HashMap::new());
-        env.insert_variable("x".to_string(), Expression::IntegerLiteral(-5));
-
-        let if_stmt = Statement::If {
-            condition: Expression::BinaryOp {
-                op: BinaryOperator::GreaterThan,
-                left: Box::new(Expression::VariableRef("x".to_string())),
-                right: Box::new(Expression::IntegerLiteral(0)),
-            },
-            body: vec![Statement::VariableDeclaration {
-                name: "result".to_string(),
-                var_type: Type::I32,
-                value: Expression::IntegerLiteral(42),
-            }],
-            else_body: Some(vec![Statement::VariableDeclaration {
-                name: "else_result".to_string(),
-                var_type: Type::I32,
-                value: Expression::IntegerLiteral(99),
-            }]),
-        };
-
-        let result = evaluate_statement(&if_stmt, &mut env);
-        assert!(result.is_ok());
-        assert!(env.get("result").is_none()); // Should not be executed
-        assert!(env.get("else_result").is_some()); // Should be executed
+        let program = check_with_x(
+            "    if (x > 0) {\n        return 42;\n    } else {\n        return 99;\n    };",
+        );
+        assert_eq!(call_with_x(&program, -5), Ok(Some(Value::Int(99))));
+    }
+
+    #[test]
+    fn test_sibling_blocks_use_separate_slots() {
+        let program = check_with_x(
+            "    if (x > 0) {\n        label: i32 = 1;\n        return label;\n    } else {\n        label: i32 = 2;\n        return label;\n    };",
+        );
+        assert_eq!(program.functions[0].variables.len(), 3);
+        assert_eq!(call_with_x(&program, 5), Ok(Some(Value::Int(1))));
+        assert_eq!(call_with_x(&program, -5), Ok(Some(Value::Int(2))));
+    }
+
+    #[test]
+    fn test_statement_call_does_not_return_from_caller() {
+        let program = check(
+            r#"
+note: function() -> void {
+    return;
+};
+
+main: function() -> i32 {
+    note();
+    return 0;
+};
+"#,
+        );
+
+        let result =
+            with_context(|context| evaluate_function(1, vec![], &program, context)).unwrap();
+        assert_eq!(result, Some(Value::Int(0)));
+    }
+
+    #[test]
+    fn test_void_function_returns_implicitly_or_with_bare_return() {
+        let program = check(
+            r#"
+log: function() -> void {
+    x: i32 = 1;
+};
+
+early: function() -> void {
+    return;
+};
+
+main: function() -> i32 {
+    return 0;
+};
+"#,
+        );
+
+        for function in [0, 1] {
+            assert_eq!(
+                with_context(|context| evaluate_function(function, vec![], &program, context)),
+                Ok(None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_void_function_must_return_a_value() {
+        let mut program = check(
+            r#"
+broken: function() -> i32 {
+    return 1;
+};
+
+main: function() -> i32 {
+    return broken();
+};
+"#,
+        );
+        // The type checker rejects such a function, so its body is removed by hand
+        program.functions[0].body.clear();
+
+        let result = with_context(|context| evaluate_function(0, vec![], &program, context));
+        assert_eq!(
+            result,
+            Err(
+                RuntimeError::from("Function 'broken' did not return a value".to_string())
+                    .in_frame("broken", None, vec![])
+            )
+        );
+    }
+
+    #[test]
+    fn test_evaluate_interpolated_string() {
+        let program = check(
+            r#"
+f: function(count: i32, max: string) -> string {
+    return "total: {int_to_string(count)} of {max}";
+};
+
+main: function() -> i32 {
+    return 0;
+};
+"#,
+        );
+
+        let args = vec![Value::Int(3), Value::Str("10".to_string())];
         assert_eq!(
-            env.get("else_result").unwrap(),
-            &Expression::IntegerLiteral(99)
+            with_context(|context| evaluate_function(0, args, &program, context)),
+            Ok(Some(Value::Str("total: 3 of 10".to_string())))
         );
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_is_truthy() {
-        assert_eq!(is_truthy(&Expression::IntegerLiteral(0)).unwrap(), false);
-        assert_eq!(is_truthy(&Expression::IntegerLiteral(1)).unwrap(), true);
-        assert_eq!(is_truthy(&Expression::IntegerLiteral(-1)).unwrap(), true);
-        assert_eq!(is_truthy(&Expression::IntegerLiteral(42)).unwrap(), true);
+        assert_eq!(is_truthy(&Value::Int(0)).unwrap(), false);
+        assert_eq!(is_truthy(&Value::Int(1)).unwrap(), true);
+        assert_eq!(is_truthy(&Value::Int(-1)).unwrap(), true);
+        assert_eq!(is_truthy(&Value::Int(42)).unwrap(), true);
     }
 
     #[test]
     fn test_evaluate_comparison_operators() {
-        let greater = evaluate_binary_op(
-            &BinaryOperator::GreaterThan,
-            &Expression::IntegerLiteral(5),
-            &Expression::IntegerLiteral(3),
-        )
-        .unwrap();
-        assert_eq!(greater, Expression::IntegerLiteral(1));
-
-        let less = evaluate_binary_op(
-            &BinaryOperator::LessThan,
-            &Expression::IntegerLiteral(3),
-            &Expression::IntegerLiteral(5),
-        )
-        .unwrap();
-        assert_eq!(less, Expression::IntegerLiteral(1));
-
-        let equal = evaluate_binary_op(
-            &BinaryOperator::Equal,
-            &Expression::IntegerLiteral(5),
-            &Expression::IntegerLiteral(5),
-        )
-        .unwrap();
-        assert_eq!(equal, Expression::IntegerLiteral(1));
+        let greater =
+            evaluate_binary_op(&BinaryOperator::GreaterThan, &Value::Int(5), &Value::Int(3))
+                .unwrap();
+        assert_eq!(greater, Value::Int(1));
+
+        let less =
+            evaluate_binary_op(&BinaryOperator::LessThan, &Value::Int(3), &Value::Int(5)).unwrap();
+        assert_eq!(less, Value::Int(1));
+
+        let equal =
+            evaluate_binary_op(&BinaryOperator::Equal, &Value::Int(5), &Value::Int(5)).unwrap();
+        assert_eq!(equal, Value::Int(1));
     }
 }
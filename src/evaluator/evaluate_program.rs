@@ -1,30 +1,244 @@
-use std::collections::HashMap;
-
-use crate::ast::environment_struct::Environment;
-use crate::ast::expression_struct::Expression;
-use crate::ast::program_struct::Program;
+use crate::ast::typed_program_struct::TypedProgram;
 use crate::evaluator::evaluate_function::evaluate_function;
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::input_source::StdinSource;
+use crate::evaluator::output_sink::StdoutSink;
+use crate::evaluator::runtime_error::{RuntimeError, RuntimeErrorKind};
+use crate::evaluator::value::Value;
 
 ///// Evaluates the program starting from the `main` function.
 ///// Returns the final i32 return value of `main`, or an error if evaluation fails.
-pub fn evaluate_program(program: &Program) -> Result<i32, String> {
-    let mut env = Environment {
-        variables: HashMap::new(),
-        functions: program
-            .functions
-            .iter()
-            .map(|f| (f.name.clone(), f))
-            .collect(),
-    };
+///// Program output is written to stdout and input is read from stdin.
+///// `program` must be the result of `type_check_program`.
+pub fn evaluate_program(program: &TypedProgram) -> Result<i32, RuntimeError> {
+    let mut output = StdoutSink;
+    let mut input = StdinSource;
+    evaluate_program_with_context(
+        program,
+        &mut EvaluationContext::new(&mut output, &mut input),
+    )
+}
 
-    let main_fn = env
-        .get_function("main")
+/// Evaluates the program like `evaluate_program`, using the given context,
+/// e.g. to capture program output in a `BufferSink` or script its input.
+pub fn evaluate_program_with_context(
+    program: &TypedProgram,
+    context: &mut EvaluationContext<'_>,
+) -> Result<i32, RuntimeError> {
+    let main_fn = program
+        .find_function("main")
         .ok_or_else(|| "main function not found".to_string())?;
 
-    let result = evaluate_function(main_fn, vec![], &mut env)?;
-    if let Expression::IntegerLiteral(code) = result {
+    let result = evaluate_function(main_fn, vec![], program, context)?;
+    if let Some(Value::Int(code)) = result {
         Ok(code)
     } else {
-        Err("main function did not return an integer".to_string())
+        Err(RuntimeErrorKind::Other("main function did not return an integer".to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::evaluation_context::DEFAULT_MAX_CALL_DEPTH;
+    use crate::evaluator::input_source::ScriptedInput;
+    use crate::evaluator::output_sink::BufferSink;
+    use crate::parser::parse_program::parse_program;
+    use crate::type_checker::type_check_program::type_check_program;
+
+    fn check(source: &str) -> TypedProgram {
+        type_check_program(&parse_program(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_input_and_output_go_through_the_context() {
+        let program = check(
+            r#"
+greet: function(name: string) -> void {
+    print("Hello, {name}!");
+};
+
+main: function() -> i32 {
+    greet(read_line());
+    print(int_to_string(string_to_int("41") + 1));
+    return 3;
+};
+"#,
+        );
+
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::new(["Ada"]);
+        let exit_code = evaluate_program_with_context(
+            &program,
+            &mut EvaluationContext::new(&mut output, &mut input),
+        );
+        assert_eq!(exit_code, Ok(3));
+        assert_eq!(output.contents(), "Hello, Ada!\n42\n");
+    }
+
+    /// Type-checks `countdown(n)`, which recurses `n` times, called from `main` with `depth`.
+    /// The recursive call is not a tail call, so every level nests a new call.
+    fn countdown_program(depth: i32) -> TypedProgram {
+        check(&format!(
+            r#"
+countdown: function(n: i32) -> i32 {{
+    if (n > 0) {{
+        rest: i32 = countdown(n - 1);
+        return rest;
+    }};
+    return 0;
+}};
+
+main: function() -> i32 {{
+    return countdown({});
+}};
+"#,
+            depth
+        ))
+    }
+
+    fn run_with_depth(
+        program: &TypedProgram,
+        max_call_depth: usize,
+    ) -> Result<i32, RuntimeErrorKind> {
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context =
+            EvaluationContext::new(&mut output, &mut input).with_max_call_depth(max_call_depth);
+        let result = evaluate_program_with_context(program, &mut context);
+        assert_eq!(context.call_depth, 0);
+        result.map_err(|error| error.kind)
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        // `main` plus 49 nested calls of `countdown` (n = 48 down to 0)
+        let program = countdown_program(48);
+        assert_eq!(run_with_depth(&program, 50), Ok(0));
+        assert_eq!(
+            run_with_depth(&program, 49),
+            Err(RuntimeErrorKind::StackOverflow {
+                function: "countdown".to_string(),
+                max_depth: 49,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deep_recursion_does_not_use_the_host_stack() {
+        // `main` plus 100001 nested calls of `countdown`, far deeper than the
+        // stack of the test thread could hold if every call nested a Rust call
+        let program = countdown_program(100_000);
+        assert_eq!(run_with_depth(&program, 100_002), Ok(0));
+    }
+
+    #[test]
+    fn test_fuel_and_deadline_limits() {
+        let program = countdown_program(100);
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+
+        let mut context = EvaluationContext::new(&mut output, &mut input).with_fuel(100);
+        assert_eq!(
+            evaluate_program_with_context(&program, &mut context).map_err(|error| error.kind),
+            Err(RuntimeErrorKind::FuelExhausted { steps: 100 })
+        );
+
+        let mut context = EvaluationContext::new(&mut output, &mut input)
+            .with_time_limit(std::time::Duration::ZERO);
+        match evaluate_program_with_context(&program, &mut context).map_err(|error| error.kind) {
+            Err(RuntimeErrorKind::DeadlineExceeded { steps, .. }) => assert_eq!(steps, 256),
+            other => panic!("Expected the deadline to be exceeded, got {:?}", other),
+        }
+
+        let mut context = EvaluationContext::new(&mut output, &mut input).with_fuel(10_000);
+        assert_eq!(evaluate_program_with_context(&program, &mut context), Ok(0));
+        assert!(context.steps > 256);
+    }
+
+    #[test]
+    fn test_runtime_error_backtrace() {
+        let program = check(
+            r#"
+divide: function(a: i32, b: i32) -> i32 {
+    return a / b;
+};
+
+main: function() -> i32 {
+    zero: i32 = 0;
+    if (zero < 1) {
+        result: i32 = divide(10, zero);
+    };
+    return 0;
+};
+"#,
+        );
+
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let error = evaluate_program_with_context(
+            &program,
+            &mut EvaluationContext::new(&mut output, &mut input),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::DivisionByZero);
+        assert_eq!(error.backtrace[0].args[1], ("b".to_string(), Value::Int(0)));
+        assert_eq!(
+            error.to_string(),
+            "Division by zero\n  at divide(a = 10, b = 0), line 3, return statement\n  at main(), line 9, declaration of 'result'"
+        );
+    }
+
+    #[test]
+    fn test_runaway_recursion_stops_at_the_default_depth() {
+        let program = check(
+            r#"
+forever: function(n: i32) -> i32 {
+    return forever(n + 1) + 1;
+};
+
+main: function() -> i32 {
+    return forever(0);
+};
+"#,
+        );
+
+        let error = evaluate_program(&program).unwrap_err();
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::StackOverflow {
+                function: "forever".to_string(),
+                max_depth: DEFAULT_MAX_CALL_DEPTH,
+            }
+        );
+    }
+
+    #[test]
+    fn test_self_tail_calls_run_in_constant_stack() {
+        let program = check(
+            r#"
+countdown: function(n: i32, steps: i32) -> i32 {
+    if (n == 0) {
+        return steps;
+    };
+    return countdown(n - 1, steps + 1);
+};
+
+main: function() -> i32 {
+    steps: i32 = countdown(1000000, 0);
+    print(int_to_string(steps));
+    return 0;
+};
+"#,
+        );
+
+        // Takes far more iterations than the call depth limit allows nested calls
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context = EvaluationContext::new(&mut output, &mut input).with_max_call_depth(10);
+        assert_eq!(evaluate_program_with_context(&program, &mut context), Ok(0));
+        assert_eq!(context.call_depth, 0);
+        assert_eq!(output.contents(), "1000000\n");
     }
 }
@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+use crate::evaluator::input_source::InputSource;
+use crate::evaluator::output_sink::OutputSink;
+use crate::evaluator::runtime_error::{RuntimeError, RuntimeErrorKind};
+
+/// Default limit on nested function calls.
+///
+/// Both backends keep calls on the heap rather than the host stack, so the
+/// limit only stops a runaway recursion before it uses up memory.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// Number of steps between two checks of the deadline, so the clock is not
+/// read on every step.
+const DEADLINE_CHECK_INTERVAL: u64 = 256;
+
+/// State shared by every function call during one evaluation, such as where
+/// program output goes and where input comes from. Passed alongside the
+/// per-call `Environment`.
+pub struct EvaluationContext<'a> {
+    pub output: &'a mut dyn OutputSink,
+    pub input: &'a mut dyn InputSource,
+    /// Maximum number of nested user function calls, including `main`.
+    pub max_call_depth: usize,
+    /// Number of user function calls currently active.
+    pub call_depth: usize,
+    /// Maximum number of evaluation steps, or `None` for no limit.
+    pub fuel: Option<u64>,
+    /// Point in time after which evaluation stops, or `None` for no limit.
+    pub deadline: Option<Instant>,
+    /// Number of steps taken so far: statements and expressions evaluated by
+    /// the tree-walker, or instructions executed by the bytecode VM.
+    pub steps: u64,
+    /// When the context was created, to report elapsed time.
+    pub started: Instant,
+}
+
+impl<'a> EvaluationContext<'a> {
+    pub fn new(output: &'a mut dyn OutputSink, input: &'a mut dyn InputSource) -> Self {
+        Self {
+            output,
+            input,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            call_depth: 0,
+            fuel: None,
+            deadline: None,
+            steps: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Sets the maximum call depth.
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = max_call_depth;
+        self
+    }
+
+    /// Limits evaluation to the given number of steps.
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = Some(fuel);
+        self
+    }
+
+    /// Stops evaluation once `time_limit` has passed from now.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.deadline = Some(Instant::now() + time_limit);
+        self
+    }
+
+    /// Counts one evaluation step, failing once the fuel or the deadline is exhausted.
+    pub fn step(&mut self) -> Result<(), RuntimeError> {
+        self.steps += 1;
+
+        if let Some(fuel) = self.fuel
+            && self.steps > fuel
+        {
+            return Err(RuntimeErrorKind::FuelExhausted { steps: fuel }.into());
+        }
+
+        if let Some(deadline) = self.deadline
+            && self.steps.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && Instant::now() >= deadline
+        {
+            return Err(RuntimeErrorKind::DeadlineExceeded {
+                steps: self.steps,
+                elapsed: self.started.elapsed(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! Sources of program input read by `read_line`.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/// Supplies lines of input to a running program.
+pub trait InputSource {
+    /// Reads the next line without its line terminator, or `None` at end of input.
+    fn read_line(&mut self) -> Result<Option<String>, String>;
+}
+
+/// Reads input from the process's standard input.
+#[derive(Default)]
+pub struct StdinSource;
+
+impl InputSource for StdinSource {
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        let mut line = String::new();
+        let read = io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|error| format!("Failed to read input: {}", error))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.strip_suffix('\n').unwrap_or(&line);
+        Ok(Some(
+            trimmed.strip_suffix('\r').unwrap_or(trimmed).to_string(),
+        ))
+    }
+}
+
+/// Serves a fixed list of lines, e.g. to script input in tests.
+#[derive(Default)]
+pub struct ScriptedInput {
+    lines: VecDeque<String>,
+}
+
+impl ScriptedInput {
+    pub fn new<I, S>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            lines: lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        Ok(self.lines.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_input_serves_lines_in_order() {
+        let mut input = ScriptedInput::new(["first", "second"]);
+        assert_eq!(input.read_line(), Ok(Some("first".to_string())));
+        assert_eq!(input.read_line(), Ok(Some("second".to_string())));
+        assert_eq!(input.read_line(), Ok(None));
+    }
+}
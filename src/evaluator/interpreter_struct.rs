@@ -0,0 +1,202 @@
+//! Calls simple_lang functions from Rust, e.g. to use a program as a plugin.
+
+use crate::ast::program_struct::Program;
+use crate::ast::typed_program_struct::TypedProgram;
+use crate::evaluator::evaluate_function::evaluate_function;
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::input_source::StdinSource;
+use crate::evaluator::output_sink::StdoutSink;
+use crate::evaluator::runtime_error::RuntimeError;
+use crate::evaluator::value::Value;
+use crate::type_checker::type_check_program::type_check_library;
+use crate::type_checker::type_error_struct::TypeError;
+
+/// A loaded program whose functions can be called any number of times.
+///
+/// The program is type-checked once, when the interpreter is built. Calls
+/// do not share any state, so each one behaves as if it were the first.
+pub struct Interpreter {
+    program: TypedProgram,
+}
+
+impl Interpreter {
+    /// Type-checks the program with `type_check_library`, so it does not
+    /// need a `main` function, and builds an interpreter for it.
+    pub fn new(program: &Program) -> Result<Self, Vec<TypeError>> {
+        Ok(Self {
+            program: type_check_library(program)?,
+        })
+    }
+
+    /// Calls the function `name` with the given arguments and returns its
+    /// result, or `None` for a void function, which only runs for its effects.
+    /// Output is written to stdout and input is read from stdin.
+    pub fn call(&self, name: &str, args: Vec<Value>) -> Result<Option<Value>, RuntimeError> {
+        let mut output = StdoutSink;
+        let mut input = StdinSource;
+        self.call_with_context(
+            name,
+            args,
+            &mut EvaluationContext::new(&mut output, &mut input),
+        )
+    }
+
+    /// Calls the function like `call`, using the given context, e.g. to
+    /// capture output or limit the steps a call may take.
+    ///
+    /// The arguments must match the function's parameter types.
+    pub fn call_with_context(
+        &self,
+        name: &str,
+        args: Vec<Value>,
+        context: &mut EvaluationContext<'_>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let index = self
+            .program
+            .find_function(name)
+            .ok_or_else(|| format!("Function '{}' not found", name))?;
+        let function = &self.program.functions[index];
+
+        if args.len() != function.params.len() {
+            return Err(format!(
+                "Expected {} arguments but got {}",
+                function.params.len(),
+                args.len()
+            )
+            .into());
+        }
+        for (param, arg) in function.params.iter().zip(&args) {
+            let param = &function.variables[param.0];
+            if arg.value_type() != param.var_type {
+                return Err(format!(
+                    "Argument '{}' of '{}' must be {:?} but got {:?}",
+                    param.name,
+                    name,
+                    param.var_type,
+                    arg.value_type()
+                )
+                .into());
+            }
+        }
+
+        evaluate_function(index, args, &self.program, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::input_source::ScriptedInput;
+    use crate::evaluator::output_sink::BufferSink;
+    use crate::evaluator::runtime_error::RuntimeErrorKind;
+    use crate::parser::parse_program::parse_program;
+
+    const PLUGIN: &str = r#"
+add: function(a: i32, b: i32) -> i32 {
+    return a + b;
+};
+
+greet: function(name: string) -> string {
+    print("greeting {name}");
+    return "Hello, {name}!";
+};
+
+divide: function(a: i32, b: i32) -> i32 {
+    return a / b;
+};
+
+log: function(text: string) -> void {
+    print(text);
+};
+"#;
+
+    fn interpreter() -> Interpreter {
+        Interpreter::new(&parse_program(PLUGIN).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_calls_functions_repeatedly() {
+        let interpreter = interpreter();
+        for n in 0..3 {
+            assert_eq!(
+                interpreter.call("add", vec![Value::Int(n), Value::Int(40)]),
+                Ok(Some(Value::Int(n + 40)))
+            );
+        }
+
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context = EvaluationContext::new(&mut output, &mut input);
+        for name in ["Ada", "Grace"] {
+            assert_eq!(
+                interpreter.call_with_context(
+                    "greet",
+                    vec![Value::Str(name.to_string())],
+                    &mut context
+                ),
+                Ok(Some(Value::Str(format!("Hello, {}!", name))))
+            );
+        }
+        assert_eq!(output.contents(), "greeting Ada\ngreeting Grace\n");
+    }
+
+    #[test]
+    fn test_void_functions_run_for_their_effects() {
+        let interpreter = interpreter();
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context = EvaluationContext::new(&mut output, &mut input);
+        assert_eq!(
+            interpreter.call_with_context("log", vec![Value::Str("hi".to_string())], &mut context),
+            Ok(None)
+        );
+        assert_eq!(output.contents(), "hi\n");
+    }
+
+    #[test]
+    fn test_type_errors_are_reported_when_built() {
+        let program =
+            parse_program("broken: function() -> i32 {\n    return \"zero\";\n};\n").unwrap();
+        let errors = Interpreter::new(&program)
+            .err()
+            .expect("Expected type errors");
+        assert_eq!(
+            errors[0].to_string(),
+            "Line 2: In function 'broken', return statement: Return type mismatch: expected I32, found String"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_calls() {
+        let interpreter = interpreter();
+        let message = |name: &str, args: Vec<Value>| match interpreter.call(name, args) {
+            Err(RuntimeError {
+                kind: RuntimeErrorKind::Other(message),
+                ..
+            }) => message,
+            other => panic!("Expected an error, got {:?}", other),
+        };
+
+        assert_eq!(message("missing", vec![]), "Function 'missing' not found");
+        assert_eq!(
+            message("add", vec![Value::Int(1)]),
+            "Expected 2 arguments but got 1"
+        );
+        assert_eq!(
+            message("add", vec![Value::Int(1), Value::Str("2".to_string())]),
+            "Argument 'b' of 'add' must be I32 but got String"
+        );
+        assert_eq!(message("log", vec![]), "Expected 1 arguments but got 0");
+    }
+
+    #[test]
+    fn test_runtime_errors_have_a_backtrace() {
+        let error = interpreter()
+            .call("divide", vec![Value::Int(1), Value::Int(0)])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Division by zero\n  at divide(a = 1, b = 0), line 12, return statement"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Destinations for program output written by `print`.
+
+use std::io::{self, Write};
+
+/// Receives each line a program prints.
+pub trait OutputSink {
+    /// Writes one line of output; `line` does not include the newline.
+    fn write_line(&mut self, line: &str) -> Result<(), String>;
+}
+
+/// Writes output to the process's standard output.
+#[derive(Default)]
+pub struct StdoutSink;
+
+impl OutputSink for StdoutSink {
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        writeln!(io::stdout(), "{}", line)
+            .map_err(|error| format!("Failed to write output: {}", error))
+    }
+}
+
+/// Collects output in memory, e.g. to inspect it in tests.
+#[derive(Default)]
+pub struct BufferSink {
+    pub lines: Vec<String>,
+}
+
+impl BufferSink {
+    /// Returns the collected output with each line terminated by a newline.
+    pub fn contents(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+}
+
+impl OutputSink for BufferSink {
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        self.lines.push(line.to_string());
+        Ok(())
+    }
+}
+
+/// Passes every line to a callback, e.g. to forward output to a host application.
+pub struct CallbackSink<F: FnMut(&str)> {
+    callback: F,
+}
+
+impl<F: FnMut(&str)> CallbackSink<F> {
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F: FnMut(&str)> OutputSink for CallbackSink<F> {
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        (self.callback)(line);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_sink_collects_lines() {
+        let mut sink = BufferSink::default();
+        sink.write_line("first").unwrap();
+        sink.write_line("second").unwrap();
+        assert_eq!(sink.lines, vec!["first", "second"]);
+        assert_eq!(sink.contents(), "first\nsecond\n");
+    }
+
+    #[test]
+    fn test_callback_sink_forwards_lines() {
+        let mut received = Vec::new();
+        {
+            let mut sink = CallbackSink::new(|line: &str| received.push(line.to_uppercase()));
+            sink.write_line("hello").unwrap();
+        }
+        assert_eq!(received, vec!["HELLO"]);
+    }
+}
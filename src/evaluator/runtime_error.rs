@@ -0,0 +1,233 @@
+//! Errors that stop a running program, with a backtrace of simple_lang frames.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::evaluator::value::Value;
+
+/// Number of frames shown at each end of a long backtrace.
+const BACKTRACE_EDGE_FRAMES: usize = 10;
+
+/// An error raised while evaluating a program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    /// The active calls when the error occurred, innermost first.
+    pub backtrace: Vec<Frame>,
+    /// Line and description of the statement being executed in the
+    /// innermost frame not yet recorded.
+    pending_statement: Option<(usize, String)>,
+}
+
+/// What went wrong at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    /// The call depth limit was reached when calling `function`.
+    StackOverflow {
+        function: String,
+        max_depth: usize,
+    },
+    /// The step budget ran out after `steps` evaluation steps.
+    FuelExhausted {
+        steps: u64,
+    },
+    /// The deadline passed after `steps` evaluation steps and `elapsed` time.
+    DeadlineExceeded {
+        steps: u64,
+        elapsed: Duration,
+    },
+    DivisionByZero,
+    /// An arithmetic operation overflowed i32, e.g. `operation` is "addition".
+    IntegerOverflow {
+        operation: String,
+    },
+    /// Any other runtime failure, such as invalid input to a builtin.
+    Other(String),
+}
+
+/// One active simple_lang function call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub function: String,
+    /// Path of the file that defines the function, if it was loaded from a file.
+    pub file: Option<String>,
+    /// Source line of the statement the function was executing: where the
+    /// error occurred in the innermost frame, and the call site in the others.
+    pub line: Option<usize>,
+    /// The statement the function was executing, e.g. `declaration of 'x'`.
+    pub statement: Option<String>,
+    /// Parameter names and the argument values the function was called with.
+    pub args: Vec<(String, Value)>,
+}
+
+impl RuntimeError {
+    pub fn new(kind: RuntimeErrorKind) -> Self {
+        Self {
+            kind,
+            backtrace: Vec::new(),
+            pending_statement: None,
+        }
+    }
+
+    /// Records the line and description of the statement that was executing
+    /// when the error occurred. Only the innermost statement of each frame is kept.
+    pub fn at_statement(mut self, line: usize, statement: impl FnOnce() -> String) -> Self {
+        if self.pending_statement.is_none() {
+            self.pending_statement = Some((line, statement()));
+        }
+        self
+    }
+
+    /// Adds the frame of a function call the error propagated out of.
+    /// `file` is the path of the file that defines the function, if known.
+    pub fn in_frame(
+        mut self,
+        function: &str,
+        file: Option<&str>,
+        args: Vec<(String, Value)>,
+    ) -> Self {
+        let (line, statement) = self.pending_statement.take().unzip();
+        self.backtrace.push(Frame {
+            function: function.to_string(),
+            file: file.map(str::to_string),
+            line,
+            statement,
+            args,
+        });
+        self
+    }
+}
+
+impl From<RuntimeErrorKind> for RuntimeError {
+    fn from(kind: RuntimeErrorKind) -> Self {
+        RuntimeError::new(kind)
+    }
+}
+
+impl From<String> for RuntimeError {
+    fn from(message: String) -> Self {
+        RuntimeError::new(RuntimeErrorKind::Other(message))
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    /// Formats the error followed by one `at` line per frame, innermost first.
+    /// Long backtraces, e.g. from a stack overflow, are shortened in the middle.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        let frames = self.backtrace.len();
+        for (index, frame) in self.backtrace.iter().enumerate() {
+            if frames > 2 * BACKTRACE_EDGE_FRAMES && index >= BACKTRACE_EDGE_FRAMES {
+                if index == BACKTRACE_EDGE_FRAMES {
+                    write!(
+                        f,
+                        "\n  ... {} more frames ...",
+                        frames - 2 * BACKTRACE_EDGE_FRAMES
+                    )?;
+                }
+                if index < frames - BACKTRACE_EDGE_FRAMES {
+                    continue;
+                }
+            }
+            write!(f, "\n  at {}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|(name, value)| match value {
+                Value::Str(text) => format!("{} = {:?}", name, text),
+                Value::Int(_) => format!("{} = {}", name, value),
+            })
+            .collect();
+        write!(f, "{}({})", self.function, args.join(", "))?;
+        // Positions are written like those of a type error's `Location`
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, ", {}:{}", file, line)?,
+            (Some(file), None) => write!(f, ", {}", file)?,
+            (None, Some(line)) => write!(f, ", line {}", line)?,
+            (None, None) => {}
+        }
+        if let Some(statement) = &self.statement {
+            write!(f, ", {}", statement)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeErrorKind::StackOverflow {
+                function,
+                max_depth,
+            } => write!(
+                f,
+                "Stack overflow: call to '{}' exceeds the maximum call depth of {}",
+                function, max_depth
+            ),
+            RuntimeErrorKind::FuelExhausted { steps } => {
+                write!(f, "Execution budget exhausted after {} steps", steps)
+            }
+            RuntimeErrorKind::DeadlineExceeded { steps, elapsed } => write!(
+                f,
+                "Time limit exceeded after {} steps ({} ms)",
+                steps,
+                elapsed.as_millis()
+            ),
+            RuntimeErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeErrorKind::IntegerOverflow { operation } => {
+                write!(f, "Integer overflow on {}", operation)
+            }
+            RuntimeErrorKind::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_backtraces_are_shortened() {
+        let mut error = RuntimeError::new(RuntimeErrorKind::DivisionByZero);
+        for depth in 0..25 {
+            error = error
+                .at_statement(3, || "return statement".to_string())
+                .in_frame(
+                    &format!("f{}", depth),
+                    None,
+                    vec![("n".to_string(), Value::Int(depth))],
+                );
+        }
+
+        let text = error.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 1 + 10 + 1 + 10);
+        assert_eq!(lines[1], "  at f0(n = 0), line 3, return statement");
+        assert_eq!(lines[11], "  ... 5 more frames ...");
+        assert_eq!(lines[12], "  at f15(n = 15), line 3, return statement");
+    }
+
+    #[test]
+    fn test_frames_show_the_file_like_type_errors() {
+        let error = RuntimeError::new(RuntimeErrorKind::DivisionByZero)
+            .at_statement(4, || "return statement".to_string())
+            .in_frame(
+                "math.divide",
+                Some("util/math.lang"),
+                vec![("b".to_string(), Value::Int(0))],
+            )
+            .in_frame("main", Some("main.lang"), vec![]);
+        assert_eq!(
+            error.to_string(),
+            "Division by zero\n  at math.divide(b = 0), util/math.lang:4, return statement\n  at main(), main.lang"
+        );
+    }
+}
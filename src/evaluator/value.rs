@@ -0,0 +1,46 @@
+//! Runtime values produced by the evaluator.
+
+use std::fmt;
+
+use crate::ast::type_struct::Type;
+
+/// A value computed at runtime.
+///
+/// Kept separate from `Expression` so the evaluator can grow new kinds of
+/// values without adding them to the syntax tree.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Int(i32),
+    Str(String),
+}
+
+impl Value {
+    /// Returns the simple_lang type of the value.
+    pub fn value_type(&self) -> Type {
+        match self {
+            Value::Int(_) => Type::I32,
+            Value::Str(_) => Type::String,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value the way `print` shows it: strings without quotes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Str(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::Int(-3).to_string(), "-3");
+        assert_eq!(Value::Str("hi there".to_string()).to_string(), "hi there");
+    }
+}
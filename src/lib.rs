@@ -1,13 +1,27 @@
 // Declare modules explicitly
 pub mod ast {
     pub mod binary_operator_struct;
+    pub mod callee_struct;
+    pub mod constant_struct;
     pub mod environment_struct;
     pub mod expression_struct;
     pub mod function_struct;
     pub mod parameter_struct;
     pub mod program_struct;
     pub mod statement_struct;
+    pub mod string_part_struct;
     pub mod type_struct;
+    pub mod typed_expression_struct;
+    pub mod typed_function_struct;
+    pub mod typed_program_struct;
+    pub mod typed_statement_struct;
+    pub mod typed_string_part_struct;
+    pub mod variable_struct;
+}
+
+pub mod builtins {
+    pub mod builtin_registry;
+    pub mod builtin_struct;
 }
 
 pub mod parser {
@@ -18,14 +32,34 @@ pub mod parser {
 }
 
 pub mod evaluator {
+    pub mod backend_struct;
     pub mod evaluate_function;
     pub mod evaluate_program;
+    pub mod evaluation_context;
+    pub mod input_source;
+    pub mod interpreter_struct;
+    pub mod output_sink;
+    pub mod runtime_error;
+    pub mod value;
+}
+
+pub mod vm {
+    pub mod bytecode_program_struct;
+    pub mod compile_program;
+    pub mod instruction_struct;
+    pub mod run_bytecode;
 }
 
 pub mod source {
+    pub mod load_program;
     pub mod read_source_file;
 }
 
 pub mod type_checker {
+    pub mod check_warnings;
+    pub mod evaluate_constant;
+    pub mod suggest_name;
     pub mod type_check_program;
+    pub mod type_error_struct;
+    pub mod warning_struct;
 }
@@ -1,10 +1,12 @@
 use crate::ast::expression_struct::Expression;
 use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::string_part_struct::StringPart;
 
 ///// Parses a string expression into an `Expression` AST node.
 /////
-///// Supports integer literals, string literals, variable references,
-///// binary operations (+, -, *, /, >, <, ==), and simple function calls.
+///// Supports integer literals, string literals (optionally with `{expr}`
+///// interpolation holes), variable references, binary operations
+///// (+, -, *, /, >, <, ==), and simple function calls.
 ///// Returns a parse error string if the expression is invalid.
 
 pub fn parse_expression(expr_str: &str) -> Result<Expression, String> {
@@ -15,9 +17,12 @@ pub fn parse_expression(expr_str: &str) -> Result<Expression, String> {
         return Ok(Expression::IntegerLiteral(num));
     }
 
-    // Try parsing as string literal
-    if expr_str.starts_with('"') && expr_str.ends_with('"') && expr_str.len() >= 2 {
+    // Try parsing as string literal: the first closing quote must end the expression
+    if expr_str.len() >= 2 && expr_str.starts_with('"') && expr_str[1..].find('"') == Some(expr_str.len() - 2) {
         let content = &expr_str[1..expr_str.len() - 1];
+        if content.contains(['{', '}']) {
+            return parse_interpolated_string(content);
+        }
         return Ok(Expression::StringLiteral(content.to_string()));
     }
 
@@ -52,7 +57,7 @@ fn try_parse_binary_op(expr_str: &str) -> Result<Option<Expression>, String> {
     ];
 
     for (symbol, op_enum) in ops.iter() {
-        if let Some(index) = expr_str.find(symbol) {
+        if let Some(index) = find_top_level(expr_str, symbol) {
             let (left_str, right_str) = expr_str.split_at(index);
             let right_str = &right_str[symbol.len()..]; // skip the operator
             let left = parse_expression(left_str.trim())?;
@@ -70,26 +75,175 @@ fn try_parse_binary_op(expr_str: &str) -> Result<Option<Expression>, String> {
 
 ///// Attempts to parse a function call from a string expression.
 fn try_parse_function_call(expr_str: &str) -> Result<Option<Expression>, String> {
-    if let Some(paren_start) = expr_str.find('(') {
-        if expr_str.ends_with(')') {
-            let name = expr_str[..paren_start].trim();
-            let args_str = &expr_str[paren_start + 1..expr_str.len() - 1];
-            let arg_strings: Vec<&str> = args_str.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
-            let mut args = Vec::new();
-            for arg_str in arg_strings {
-                let expr = parse_expression(arg_str)?;
-                args.push(expr);
-            }
-            return Ok(Some(Expression::FunctionCall {
-                name: name.to_string(),
-                args,
-            }));
+    if let Some(paren_start) = expr_str.find('(')
+        && expr_str.ends_with(')')
+    {
+        let name = expr_str[..paren_start].trim();
+        let args_str = &expr_str[paren_start + 1..expr_str.len() - 1];
+        let arg_strings: Vec<&str> = split_top_level(args_str, ',').into_iter().map(str::trim).filter(|s| !s.is_empty()).collect();
+        let mut args = Vec::new();
+        for arg_str in arg_strings {
+            let expr = parse_expression(arg_str)?;
+            args.push(expr);
         }
+        return Ok(Some(Expression::FunctionCall {
+            name: name.to_string(),
+            args,
+        }));
     }
     Ok(None)
 }
 
+///// Parses the content of a string literal containing `{expr}` holes.
+/////
+///// `{{` and `}}` produce literal braces. Holes may not be empty, nested,
+///// or contain string literals.
+fn parse_interpolated_string(content: &str) -> Result<Expression, String> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = content;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        literal.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            literal.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err(format!(
+                "Unmatched '}}' in string \"{}\"; use '}}}}' for a literal brace",
+                content
+            ));
+        }
+
+        let end = tail
+            .find('}')
+            .ok_or_else(|| format!("Unclosed interpolation hole in string \"{}\"", content))?;
+        let source = tail[1..end].trim();
+        if source.is_empty() {
+            return Err(format!("Empty interpolation hole in string \"{}\"", content));
+        }
+        if source.contains(['{', '"']) {
+            return Err(format!(
+                "Interpolation hole {{{}}} may not contain braces or string literals",
+                source
+            ));
+        }
+
+        if !literal.is_empty() {
+            parts.push(StringPart::Literal(std::mem::take(&mut literal)));
+        }
+        parts.push(StringPart::Hole {
+            source: source.to_string(),
+            expression: parse_expression(source)?,
+        });
+        rest = &tail[end + 1..];
+    }
+    literal.push_str(rest);
+
+    if parts.is_empty() {
+        // Only escaped braces, no holes
+        return Ok(Expression::StringLiteral(literal));
+    }
+    if !literal.is_empty() {
+        parts.push(StringPart::Literal(literal));
+    }
+    Ok(Expression::InterpolatedString(parts))
+}
+
+///// Finds the first occurrence of `pattern` outside string literals and parentheses.
+pub fn find_top_level(s: &str, pattern: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut depth = 0;
+
+    for (index, ch) in s.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            _ if !in_string && depth == 0 && s[index..].starts_with(pattern) => {
+                return Some(index);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+///// Splits a string on `separator`, ignoring separators inside string literals
+///// and parentheses (e.g. commas in nested calls or in string arguments).
+pub fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut rest = s;
+
+    while let Some(index) = find_top_level(rest, separator.encode_utf8(&mut [0; 4])) {
+        parts.push(&s[start..start + index]);
+        start += index + separator.len_utf8();
+        rest = &s[start..];
+    }
+    parts.push(rest);
+    parts
+}
+
 ///// Checks whether a string is a valid identifier (variable or function name).
 fn is_valid_identifier(s: &str) -> bool {
     !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_') && !s.chars().next().unwrap().is_numeric()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interpolated_string() {
+        let expr = parse_expression("\"total: {count} of {int_to_string(max)}\"").unwrap();
+        assert_eq!(
+            expr,
+            Expression::InterpolatedString(vec![
+                StringPart::Literal("total: ".to_string()),
+                StringPart::Hole {
+                    source: "count".to_string(),
+                    expression: Expression::VariableRef("count".to_string()),
+                },
+                StringPart::Literal(" of ".to_string()),
+                StringPart::Hole {
+                    source: "int_to_string(max)".to_string(),
+                    expression: Expression::FunctionCall {
+                        name: "int_to_string".to_string(),
+                        args: vec![Expression::VariableRef("max".to_string())],
+                    },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces_are_plain_text() {
+        let expr = parse_expression("\"{{not a hole}}\"").unwrap();
+        assert_eq!(expr, Expression::StringLiteral("{not a hole}".to_string()));
+    }
+
+    #[test]
+    fn test_malformed_interpolation_holes() {
+        assert!(parse_expression("\"{count\"").unwrap_err().contains("Unclosed"));
+        assert!(parse_expression("\"count}\"").unwrap_err().contains("Unmatched"));
+        assert!(parse_expression("\"{ }\"").unwrap_err().contains("Empty"));
+    }
+
+    #[test]
+    fn test_separators_inside_strings_are_ignored() {
+        let expr = parse_expression("print(\"a, b - c\", add(x, 1 + 2))").unwrap();
+        match expr {
+            Expression::FunctionCall { name, args } => {
+                assert_eq!(name, "print");
+                assert_eq!(args.len(), 2);
+                assert_eq!(args[0], Expression::StringLiteral("a, b - c".to_string()));
+            }
+            other => panic!("Expected a function call, got {:?}", other),
+        }
+    }
+}
@@ -6,9 +6,10 @@ use crate::ast::statement_struct::Statement;
 use crate::ast::type_struct::Type;
 use crate::parser::parse_statement::{parse_if_statement_multiline, parse_statement};
 
-/// Parses a function definition from a slice of input lines.
+/// Parses a function definition from a slice of input lines, the first of
+/// which is line `first_line` of the source file.
 /// Works with your exact code format including multi-line if statements.
-pub fn parse_function(lines: &[&str]) -> Result<Function, String> {
+pub fn parse_function(lines: &[&str], first_line: usize) -> Result<Function, String> {
     if lines.is_empty() {
         return Err("Empty function input.".to_string());
     }
@@ -29,18 +30,24 @@ pub fn parse_function(lines: &[&str]) -> Result<Function, String> {
 
     // Parse the function body
     let body_lines = &lines[1..lines.len() - 1];
-    let body = parse_function_body_integrated(body_lines)?;
+    let body = parse_function_body_integrated(body_lines, first_line + 1)?;
 
     Ok(Function {
         name,
         params,
         return_type,
         body,
+        line: first_line,
+        file: None,
     })
 }
 
-/// Parse function body with integrated multi-line if statement handling
-fn parse_function_body_integrated(lines: &[&str]) -> Result<Vec<Statement>, String> {
+/// Parse function body with integrated multi-line if statement handling.
+/// `first_line` is the source line number of `lines[0]`.
+fn parse_function_body_integrated(
+    lines: &[&str],
+    first_line: usize,
+) -> Result<Vec<Statement>, String> {
     let mut body = Vec::new();
     let mut i = 0;
 
@@ -58,12 +65,12 @@ fn parse_function_body_integrated(lines: &[&str]) -> Result<Vec<Statement>, Stri
             // This is a multi-line if statement
             let remaining_lines = &lines[i + 1..];
             let (if_statement, lines_consumed) =
-                parse_if_statement_multiline(line, remaining_lines)?;
+                parse_if_statement_multiline(line, remaining_lines, first_line + i)?;
             body.push(if_statement);
             i += lines_consumed + 1; // +1 for the if line itself
         } else {
             // Regular single-line statement
-            let stmt = parse_statement(line)?;
+            let stmt = parse_statement(line, first_line + i)?;
             body.push(stmt);
             i += 1;
         }
@@ -142,7 +149,7 @@ mod tests {
             "};",
         ];
 
-        let result = parse_function(&lines);
+        let result = parse_function(&lines, 1);
         assert!(
             result.is_ok(),
             "Should parse successfully: {:?}",
@@ -157,7 +164,8 @@ mod tests {
             if let Statement::If {
                 condition: _,
                 body,
-                else_body,
+                else_body: _,
+                ..
             } = &function.body[0]
             {
                 assert_eq!(body.len(), 2); // print statements
@@ -180,7 +188,7 @@ mod tests {
             "};",
         ];
 
-        let result = parse_function(&lines);
+        let result = parse_function(&lines, 1);
         assert!(result.is_ok(), "Your code should parse: {:?}", result.err());
     }
 }
@@ -8,24 +8,21 @@ use std::vec::Vec;
 /// Fixed to properly handle multi-line if statements and other block constructs.
 pub fn parse_program(source: &str) -> Result<Program, String> {
     let mut functions: Vec<Function> = Vec::new();
+    let mut imports: Vec<String> = Vec::new();
 
     // Normalize line endings and preserve the normalized string
     let normalized = source.replace("\r\n", "\n");
     
-    // Filter and collect lines, preserving empty lines within functions
-    let all_lines: Vec<&str> = normalized.lines().collect();
-    let mut processed_lines = Vec::new();
-    
-    for line in all_lines {
-        let trimmed = line.trim();
-        // Keep all lines that aren't purely comment lines
-        if !trimmed.starts_with("//") {
-            processed_lines.push(line);
-        }
-    }
+    // Blank out comment lines instead of dropping them, so that the index
+    // of each line still gives its line number in the source
+    let processed_lines: Vec<&str> = normalized
+        .lines()
+        .map(|line| if line.trim().starts_with("//") { "" } else { line })
+        .collect();
 
     // Parse functions by identifying complete blocks with proper brace tracking
     let mut current_block: Vec<&str> = Vec::new();
+    let mut block_start = 0;
     let mut brace_depth = 0;
     let mut in_function = false;
     let mut i = 0;
@@ -40,6 +37,22 @@ pub fn parse_program(source: &str) -> Result<Program, String> {
             continue;
         }
 
+        // Import statements are only allowed at the top of the file
+        if !in_function && trimmed.starts_with("import ") {
+            if !functions.is_empty() {
+                return Err(format!(
+                    "Import '{}' must appear before any function definitions",
+                    trimmed
+                ));
+            }
+            imports.push(parse_import(trimmed)?);
+            i += 1;
+            continue;
+        }
+
+        if current_block.is_empty() {
+            block_start = i + 1;
+        }
         current_block.push(line);
 
         // Detect function start
@@ -52,7 +65,7 @@ pub fn parse_program(source: &str) -> Result<Program, String> {
 
             // Function complete when we return to brace_depth 0 and see };
             if brace_depth == 0 && trimmed.ends_with("};") {
-                let func = parse_function_block(&current_block)?;
+                let func = parse_function_block(&current_block, block_start)?;
                 functions.push(func);
                 current_block.clear();
                 in_function = false;
@@ -72,7 +85,26 @@ pub fn parse_program(source: &str) -> Result<Program, String> {
         return Err("Unexpected content found outside function blocks.".to_string());
     }
 
-    Ok(Program { functions })
+    Ok(Program { functions, imports })
+}
+
+/// Parses an `import "path/to/file.lang";` line and returns the quoted path.
+fn parse_import(line: &str) -> Result<String, String> {
+    let content = line
+        .strip_suffix(';')
+        .ok_or_else(|| format!("Import must end with a semicolon ';': {}", line))?;
+    let path = content["import ".len()..].trim();
+
+    if path.len() < 2 || !path.starts_with('"') || !path.ends_with('"') {
+        return Err(format!("Import path must be a string literal: {}", line));
+    }
+
+    let path = &path[1..path.len() - 1];
+    if path.is_empty() {
+        return Err("Import path cannot be empty".to_string());
+    }
+
+    Ok(path.to_string())
 }
 
 /// Counts the net brace difference in a line (opening braces - closing braces)
@@ -99,20 +131,22 @@ fn count_net_braces(line: &str) -> i32 {
     net_braces
 }
 
-/// Parses a function block from lines of source code.
+/// Parses a function block from lines of source code, the first of which
+/// is line `first_line` of the source.
 /// Enhanced to handle multi-line constructs properly.
-fn parse_function_block(lines: &[&str]) -> Result<Function, String> {
+fn parse_function_block(lines: &[&str], first_line: usize) -> Result<Function, String> {
     if lines.is_empty() {
         return Err("Empty function block".to_string());
     }
     
     // Use the enhanced function parser
-    parse_function(lines)
+    parse_function(lines, first_line)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::statement_struct::Statement;
 
     #[test]
     fn test_count_net_braces() {
@@ -122,6 +156,83 @@ mod tests {
         assert_eq!(count_net_braces("print(\"test { } test\");"), 0); // Braces in strings don't count
     }
 
+    #[test]
+    fn test_parse_program_with_imports() {
+        let source = r#"
+// Shared helpers
+import "util/math.lang";
+import "strings.lang";
+
+main: function() -> i32 {
+    return math.add(1, 2);
+};
+"#;
+        let program = parse_program(source).expect("Parse should succeed");
+        assert_eq!(program.imports, vec!["util/math.lang", "strings.lang"]);
+        assert_eq!(program.functions.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_program_rejects_late_import() {
+        let source = r#"
+main: function() -> i32 {
+    return 0;
+};
+
+import "util/math.lang";
+"#;
+        let result = parse_program(source);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("must appear before any function definitions"));
+    }
+
+    #[test]
+    fn test_parse_program_records_source_lines() {
+        let source = r#"
+// Comment lines still count
+helper: function() -> void {
+    print("helper");
+};
+
+main: function() -> i32 {
+    // Pick a value
+    x: i32 = 1;
+    if (x > 0) {
+        print("positive");
+    } else {
+        print("negative");
+    };
+    return x;
+};
+"#;
+        let program = parse_program(source).expect("Parse should succeed");
+        let main = &program.functions[1];
+        assert_eq!(program.functions[0].line, 3);
+        assert_eq!(main.line, 7);
+
+        let lines: Vec<usize> = main.body.iter().map(|stmt| stmt.line()).collect();
+        assert_eq!(lines, vec![9, 10, 15]);
+        match &main.body[1] {
+            Statement::If {
+                body,
+                else_body: Some(else_body),
+                ..
+            } => {
+                assert_eq!(body[0].line(), 11);
+                assert_eq!(else_body[0].line(), 13);
+            }
+            other => panic!("Expected an if statement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_import_requires_quoted_path() {
+        assert_eq!(parse_import("import \"a.lang\";").unwrap(), "a.lang");
+        assert!(parse_import("import a.lang;").is_err());
+        assert!(parse_import("import \"a.lang\"").is_err());
+        assert!(parse_import("import \"\";").is_err());
+    }
+
     #[test]
     fn test_parse_program_with_multiline_if() {
         let source = r#"
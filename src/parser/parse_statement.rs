@@ -3,16 +3,17 @@
 use crate::ast::expression_struct::Expression;
 use crate::ast::statement_struct::Statement;
 use crate::ast::type_struct::Type;
-use crate::parser::parse_expression::parse_expression;
+use crate::parser::parse_expression::{find_top_level, parse_expression, split_top_level};
 
 /// Parses a single statement line into a `Statement` AST node.
+/// `line_number` is the line of the statement in its source file.
 /// Enhanced to handle if statements properly.
-pub fn parse_statement(line: &str) -> Result<Statement, String> {
+pub fn parse_statement(line: &str, line_number: usize) -> Result<Statement, String> {
     let trimmed = line.trim();
 
     // Handle if statements - check for the pattern that matches your code
     if trimmed.starts_with("if (") && trimmed.contains(") {") {
-        return parse_if_statement_simple(trimmed);
+        return parse_if_statement_simple(trimmed, line_number);
     }
 
     // Must end with semicolon for non-if statements
@@ -22,15 +23,26 @@ pub fn parse_statement(line: &str) -> Result<Statement, String> {
 
     let content = &trimmed[..trimmed.len() - 1]; // remove semicolon
 
+    // Bare return from a void function
+    if content.trim_end() == "return" {
+        return Ok(Statement::Return {
+            value: None,
+            line: line_number,
+        });
+    }
+
     // Return statement
-    if content.starts_with("return ") {
-        let expr_str = content["return ".len()..].trim();
+    if let Some(expr_str) = content.strip_prefix("return ") {
+        let expr_str = expr_str.trim();
         let expr = parse_expression(expr_str)?;
-        return Ok(Statement::Return { value: expr });
+        return Ok(Statement::Return {
+            value: Some(expr),
+            line: line_number,
+        });
     }
 
     // Variable declaration (e.g., name: type = expression)
-    if let Some(idx_eq) = content.find('=') {
+    if let Some(idx_eq) = find_top_level(content, "=") {
         let (left, right) = content.split_at(idx_eq);
         let right_expr_str = right[1..].trim(); // Skip '='
 
@@ -48,24 +60,29 @@ pub fn parse_statement(line: &str) -> Result<Statement, String> {
             name,
             var_type,
             value: expr,
+            line: line_number,
         });
     }
 
     // Function call (e.g., print("hello"))
-    if let Some(idx_paren) = content.find('(') {
-        if content.ends_with(')') {
-            let name = content[..idx_paren].trim().to_string();
-            let args_str = &content[idx_paren + 1..content.len() - 1];
-            let args = parse_arguments(args_str)?;
-            return Ok(Statement::FunctionCall { name, args });
-        }
+    if let Some(idx_paren) = content.find('(')
+        && content.ends_with(')')
+    {
+        let name = content[..idx_paren].trim().to_string();
+        let args_str = &content[idx_paren + 1..content.len() - 1];
+        let args = parse_arguments(args_str)?;
+        return Ok(Statement::FunctionCall {
+            name,
+            args,
+            line: line_number,
+        });
     }
 
     Err(String::from("Unrecognized statement syntax"))
 }
 
 /// Simple if statement parser for single-line format with opening brace
-fn parse_if_statement_simple(line: &str) -> Result<Statement, String> {
+fn parse_if_statement_simple(line: &str, line_number: usize) -> Result<Statement, String> {
     let trimmed = line.trim();
 
     // Find the condition part between "if (" and ") {"
@@ -83,14 +100,16 @@ fn parse_if_statement_simple(line: &str) -> Result<Statement, String> {
         condition,
         body: Vec::new(), // Will be filled by the function parser
         else_body: None,  // Will be filled by the function parser if else exists
+        line: line_number,
     })
 }
 
 /// Parse function that handles multi-line if statements by collecting the body
-/// Enhanced to support else blocks
+/// Enhanced to support else blocks. `line_number` is the source line of `if_line`.
 pub fn parse_if_statement_multiline(
     if_line: &str,
     remaining_lines: &[&str],
+    line_number: usize,
 ) -> Result<(Statement, usize), String> {
     // Parse the condition from the first line
     let trimmed = if_line.trim();
@@ -126,16 +145,16 @@ pub fn parse_if_statement_multiline(
         }
 
         if !line_trimmed.is_empty() {
-            body_lines.push(line);
+            body_lines.push((line_number + 1 + i, line));
         }
     }
 
     // Parse if body statements
     let mut body = Vec::new();
-    for body_line in body_lines {
+    for (body_line_number, body_line) in body_lines {
         let trimmed_body = body_line.trim();
         if !trimmed_body.is_empty() {
-            let stmt = parse_statement(trimmed_body)?;
+            let stmt = parse_statement(trimmed_body, body_line_number)?;
             body.push(stmt);
         }
     }
@@ -146,7 +165,7 @@ pub fn parse_if_statement_multiline(
         let mut else_body_lines = Vec::new();
 
         // Continue from where we left off to find the end of else block
-        for &line in &remaining_lines[else_block_start..] {
+        for (i, &line) in remaining_lines.iter().enumerate().skip(else_block_start) {
             lines_consumed += 1;
             let line_trimmed = line.trim();
 
@@ -155,16 +174,16 @@ pub fn parse_if_statement_multiline(
             }
 
             if !line_trimmed.is_empty() {
-                else_body_lines.push(line);
+                else_body_lines.push((line_number + 1 + i, line));
             }
         }
 
         // Parse else body statements
         let mut else_statements = Vec::new();
-        for else_line in else_body_lines {
+        for (else_line_number, else_line) in else_body_lines {
             let trimmed_else = else_line.trim();
             if !trimmed_else.is_empty() {
-                let stmt = parse_statement(trimmed_else)?;
+                let stmt = parse_statement(trimmed_else, else_line_number)?;
                 else_statements.push(stmt);
             }
         }
@@ -179,6 +198,7 @@ pub fn parse_if_statement_multiline(
             condition,
             body,
             else_body,
+            line: line_number,
         },
         lines_consumed,
     ))
@@ -189,8 +209,8 @@ fn parse_arguments(args_str: &str) -> Result<Vec<Expression>, String> {
     if args_str.trim().is_empty() {
         return Ok(vec![]);
     }
-    args_str
-        .split(',')
+    split_top_level(args_str, ',')
+        .into_iter()
         .map(|arg| parse_expression(arg.trim()))
         .collect()
 }
@@ -209,16 +229,32 @@ fn parse_type(type_str: &str) -> Result<Type, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_return_statements() {
+        match parse_statement("return;", 1) {
+            Ok(Statement::Return { value: None, .. }) => {}
+            other => panic!("Expected bare return, got {:?}", other),
+        }
+        match parse_statement("return 42;", 1) {
+            Ok(Statement::Return {
+                value: Some(Expression::IntegerLiteral(42)),
+                ..
+            }) => {}
+            other => panic!("Expected return with value, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_if_statement_simple() {
         let line = "if (num > 0) {";
-        let result = parse_if_statement_simple(line);
+        let result = parse_if_statement_simple(line, 1);
         assert!(result.is_ok());
 
         if let Ok(Statement::If {
             condition: _,
             body,
             else_body,
+            ..
         }) = result
         {
             assert_eq!(body.len(), 0); // Body will be empty initially
@@ -237,7 +273,7 @@ mod tests {
             "};",
         ];
 
-        let result = parse_if_statement_multiline(if_line, &remaining_lines);
+        let result = parse_if_statement_multiline(if_line, &remaining_lines, 1);
         assert!(result.is_ok());
 
         if let Ok((
@@ -245,6 +281,7 @@ mod tests {
                 condition: _,
                 body,
                 else_body,
+                ..
             },
             lines_consumed,
         )) = result
@@ -267,7 +304,7 @@ mod tests {
             "};",
         ];
 
-        let result = parse_if_statement_multiline(if_line, &remaining_lines);
+        let result = parse_if_statement_multiline(if_line, &remaining_lines, 1);
         assert!(result.is_ok());
 
         if let Ok((
@@ -275,6 +312,7 @@ mod tests {
                 condition: _,
                 body,
                 else_body,
+                ..
             },
             lines_consumed,
         )) = result
@@ -0,0 +1,499 @@
+//! Loads a program from disk and resolves its `import` statements.
+//!
+//! Each imported file is read through `read_source_file`, relative to the file
+//! that imports it, and its functions are merged into the root program under
+//! a namespace taken from the file name (`util/math.lang` -> `math.add`).
+//! A file can only call its own functions, those of the files it imports
+//! directly, and builtins. Import cycles, duplicate function definitions,
+//! two different files claiming the same namespace and calls to functions
+//! that a file cannot see are reported as errors.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::ast::expression_struct::Expression;
+use crate::ast::function_struct::Function;
+use crate::ast::program_struct::Program;
+use crate::ast::statement_struct::Statement;
+use crate::ast::string_part_struct::StringPart;
+use crate::parser::parse_program::parse_program;
+use crate::source::read_source_file::read_source_file;
+
+/// Reads, parses and links the program rooted at `file_path`.
+///
+/// Functions of the root file keep their names; functions of imported files
+/// are renamed to `namespace.name`, and calls between functions of the same
+/// imported file are rewritten to the qualified name. Every function records
+/// the path of the file that defined it, for diagnostics.
+pub fn load_program(file_path: &str) -> Result<Program, String> {
+    let mut loader = ModuleLoader::default();
+    let imports = loader.load_module(Path::new(file_path), None)?;
+    loader.check_visibility()?;
+
+    Ok(Program {
+        functions: loader.functions,
+        imports,
+    })
+}
+
+/// Tracks the state of an import resolution run.
+#[derive(Default)]
+struct ModuleLoader {
+    /// Files currently being loaded, in import order, used for cycle detection.
+    loading: Vec<PathBuf>,
+    /// Files that have already been merged; importing them again is a no-op.
+    loaded: HashSet<PathBuf>,
+    /// Merged functions of all loaded files.
+    functions: Vec<Function>,
+    /// Maps each merged function name to the file that defined it.
+    origins: HashMap<String, String>,
+    /// Maps each namespace to the file it was derived from.
+    namespaces: HashMap<String, PathBuf>,
+    /// Maps each namespace to the qualified names of the functions its file defines.
+    exports: HashMap<String, Vec<String>>,
+    /// The functions of each loaded file and the names they may call.
+    scopes: Vec<FileScope>,
+}
+
+/// The functions merged from one file and the functions visible to them.
+struct FileScope {
+    file: String,
+    /// Range of the file's functions in `ModuleLoader::functions`.
+    functions: Range<usize>,
+    /// Names of the file's own functions and those of its direct imports,
+    /// as they appear in the merged program.
+    visible: HashSet<String>,
+}
+
+impl ModuleLoader {
+    /// Loads one file and, recursively, everything it imports.
+    /// Returns the import paths declared by the file itself.
+    fn load_module(&mut self, path: &Path, namespace: Option<&str>) -> Result<Vec<String>, String> {
+        let display_path = path.display().to_string();
+        let source_file = read_source_file(&display_path)?;
+
+        let canonical = path
+            .canonicalize()
+            .map_err(|error| format!("Failed to resolve '{}': {}", display_path, error))?;
+
+        if let Some(start) = self.loading.iter().position(|p| p == &canonical) {
+            let mut chain: Vec<String> = self.loading[start..]
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect();
+            chain.push(canonical.display().to_string());
+            return Err(format!("Import cycle detected: {}", chain.join(" -> ")));
+        }
+        if let Some(namespace) = namespace {
+            self.claim_namespace(namespace, &canonical)?;
+        }
+        if self.loaded.contains(&canonical) {
+            return Ok(Vec::new());
+        }
+
+        let program = parse_program(&source_file.content)
+            .map_err(|error| format!("Parse error in '{}': {}", display_path, error))?;
+
+        self.loading.push(canonical.clone());
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut visible = HashSet::new();
+        for import in &program.imports {
+            let import_path = base_dir.join(import);
+            let import_namespace = namespace_for(&import_path)?;
+            self.load_module(&import_path, Some(&import_namespace))?;
+            if let Some(exports) = self.exports.get(&import_namespace) {
+                visible.extend(exports.iter().cloned());
+            }
+        }
+        self.loading.pop();
+        self.loaded.insert(canonical);
+
+        let local_names: HashSet<String> =
+            program.functions.iter().map(|f| f.name.clone()).collect();
+
+        let first = self.functions.len();
+        for mut function in program.functions {
+            function.file = Some(display_path.clone());
+            if let Some(namespace) = namespace {
+                function.name = format!("{}.{}", namespace, function.name);
+                for_each_call(&mut function.body, &mut |name| {
+                    if local_names.contains(name.as_str()) {
+                        *name = format!("{}.{}", namespace, name);
+                    }
+                });
+            }
+            self.add_function(function, &display_path)?;
+        }
+
+        let own_names: Vec<String> = self.functions[first..]
+            .iter()
+            .map(|f| f.name.clone())
+            .collect();
+        visible.extend(own_names.iter().cloned());
+        if let Some(namespace) = namespace {
+            self.exports.insert(namespace.to_string(), own_names);
+        }
+        self.scopes.push(FileScope {
+            file: display_path,
+            functions: first..self.functions.len(),
+            visible,
+        });
+
+        Ok(program.imports)
+    }
+
+    /// Rejects calls from a file to a function of the merged program that the
+    /// file neither defines nor imports directly. Calls to names that are not
+    /// defined anywhere are left to the type checker, which suggests a fix.
+    fn check_visibility(&mut self) -> Result<(), String> {
+        for scope in &self.scopes {
+            for function in &mut self.functions[scope.functions.clone()] {
+                let mut hidden = None;
+                for_each_call(&mut function.body, &mut |name| {
+                    if hidden.is_none()
+                        && !scope.visible.contains(name.as_str())
+                        && self.origins.contains_key(name.as_str())
+                    {
+                        hidden = Some(name.clone());
+                    }
+                });
+                if let Some(name) = hidden {
+                    return Err(format!(
+                        "Function '{}' in '{}' calls '{}', which is defined in '{}' but not in that file or one of its imports",
+                        function.name, scope.file, name, self.origins[&name]
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that `namespace` belongs to `file`, rejecting a second file
+    /// with the same name, whose functions would otherwise be merged together.
+    fn claim_namespace(&mut self, namespace: &str, file: &Path) -> Result<(), String> {
+        match self.namespaces.get(namespace) {
+            Some(owner) if owner != file => Err(format!(
+                "Namespace '{}' of '{}' collides with '{}'",
+                namespace,
+                file.display(),
+                owner.display()
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.namespaces
+                    .insert(namespace.to_string(), file.to_path_buf());
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds a function to the merged program, rejecting duplicate names.
+    fn add_function(&mut self, function: Function, file_path: &str) -> Result<(), String> {
+        if let Some(previous) = self.origins.get(&function.name) {
+            return Err(format!(
+                "Duplicate definition of function '{}' in '{}' (already defined in '{}')",
+                function.name, file_path, previous
+            ));
+        }
+        self.origins
+            .insert(function.name.clone(), file_path.to_string());
+        self.functions.push(function);
+        Ok(())
+    }
+}
+
+/// Derives the namespace of an imported file from its file name.
+fn namespace_for(path: &Path) -> Result<String, String> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+
+    let valid = !stem.is_empty()
+        && stem.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !stem.chars().next().unwrap().is_numeric();
+    if !valid {
+        return Err(format!(
+            "Cannot derive a namespace from import '{}'",
+            path.display()
+        ));
+    }
+
+    Ok(stem.to_string())
+}
+
+/// Calls `visit` with the callee name of every call in the statements,
+/// e.g. to rewrite calls to functions of the same file to qualified names.
+fn for_each_call(statements: &mut [Statement], visit: &mut impl FnMut(&mut String)) {
+    for stmt in statements {
+        match stmt {
+            Statement::VariableDeclaration { value, .. } => {
+                for_each_expression_call(value, visit);
+            }
+            Statement::FunctionCall { name, args, .. } => {
+                visit(name);
+                for arg in args {
+                    for_each_expression_call(arg, visit);
+                }
+            }
+            Statement::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                for_each_expression_call(condition, visit);
+                for_each_call(body, visit);
+                if let Some(else_statements) = else_body {
+                    for_each_call(else_statements, visit);
+                }
+            }
+            Statement::Return { value, .. } => {
+                if let Some(value) = value {
+                    for_each_expression_call(value, visit);
+                }
+            }
+        }
+    }
+}
+
+fn for_each_expression_call(expr: &mut Expression, visit: &mut impl FnMut(&mut String)) {
+    match expr {
+        Expression::IntegerLiteral(_)
+        | Expression::StringLiteral(_)
+        | Expression::VariableRef(_) => {}
+        Expression::InterpolatedString(parts) => {
+            for part in parts {
+                if let StringPart::Hole { expression, .. } = part {
+                    for_each_expression_call(expression, visit);
+                }
+            }
+        }
+        Expression::BinaryOp { left, right, .. } => {
+            for_each_expression_call(left, visit);
+            for_each_expression_call(right, visit);
+        }
+        Expression::FunctionCall { name, args } => {
+            visit(name);
+            for arg in args {
+                for_each_expression_call(arg, visit);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Creates an empty scratch directory unique to the calling test.
+    fn scratch_dir(test_name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "simple_lang_load_program_{}_{}",
+            test_name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(dir: &Path, relative: &str, content: &str) {
+        let path = dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_imported_functions_are_namespaced() {
+        let dir = scratch_dir("namespaced");
+        write_file(
+            &dir,
+            "util/math.lang",
+            "add: function(a: i32, b: i32) -> i32 {\n    return a + b;\n};\n\n\
+             double: function(a: i32) -> i32 {\n    return add(a, a);\n};\n",
+        );
+        write_file(
+            &dir,
+            "main.lang",
+            "import \"util/math.lang\";\n\nmain: function() -> i32 {\n    return math.double(2);\n};\n",
+        );
+
+        let program = load_program(dir.join("main.lang").to_str().unwrap()).unwrap();
+        let names: Vec<&str> = program.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["math.add", "math.double", "main"]);
+        assert_eq!(program.imports, vec!["util/math.lang"]);
+
+        // The call inside `double` is rewritten to the qualified name
+        match &program.functions[1].body[0] {
+            Statement::Return {
+                value: Some(Expression::FunctionCall { name, .. }),
+                line,
+            } => {
+                assert_eq!(name, "math.add");
+                assert_eq!(*line, 6);
+            }
+            _ => panic!("Expected a return of a function call"),
+        }
+
+        // Each function remembers the file and line that defined it
+        let double = &program.functions[1];
+        assert!(double.file.as_ref().unwrap().ends_with("math.lang"));
+        assert_eq!(double.line, 5);
+        assert!(
+            program.functions[2]
+                .file
+                .as_ref()
+                .unwrap()
+                .ends_with("main.lang")
+        );
+    }
+
+    #[test]
+    fn test_shared_import_is_loaded_once() {
+        let dir = scratch_dir("diamond");
+        write_file(
+            &dir,
+            "base.lang",
+            "one: function() -> i32 {\n    return 1;\n};\n",
+        );
+        write_file(
+            &dir,
+            "left.lang",
+            "import \"base.lang\";\n\ntwo: function() -> i32 {\n    return base.one() + 1;\n};\n",
+        );
+        write_file(&dir, "right.lang", "import \"base.lang\";\n");
+        write_file(
+            &dir,
+            "main.lang",
+            "import \"left.lang\";\nimport \"right.lang\";\n\nmain: function() -> i32 {\n    return left.two();\n};\n",
+        );
+
+        let program = load_program(dir.join("main.lang").to_str().unwrap()).unwrap();
+        assert_eq!(program.functions.len(), 3);
+    }
+
+    #[test]
+    fn test_imported_file_cannot_call_functions_of_its_importer() {
+        let dir = scratch_dir("importer_leak");
+        write_file(
+            &dir,
+            "math.lang",
+            "twice: function(a: i32) -> i32 {\n    return helper(a) * 2;\n};\n",
+        );
+        write_file(
+            &dir,
+            "main.lang",
+            "import \"math.lang\";\n\nhelper: function(a: i32) -> i32 {\n    return a;\n};\n\n\
+             main: function() -> i32 {\n    return math.twice(helper(1));\n};\n",
+        );
+
+        let error = load_program(dir.join("main.lang").to_str().unwrap()).unwrap_err();
+        assert!(
+            error.starts_with("Function 'math.twice' in '") && error.contains("calls 'helper'"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_imports_of_imports_are_not_visible() {
+        let dir = scratch_dir("transitive_leak");
+        write_file(
+            &dir,
+            "deep.lang",
+            "one: function() -> i32 {\n    return 1;\n};\n",
+        );
+        write_file(
+            &dir,
+            "math.lang",
+            "import \"deep.lang\";\n\nhundred: function() -> i32 {\n    return deep.one() * 100;\n};\n",
+        );
+        write_file(
+            &dir,
+            "main.lang",
+            "import \"math.lang\";\n\nmain: function() -> i32 {\n    return math.hundred() + deep.one();\n};\n",
+        );
+
+        let error = load_program(dir.join("main.lang").to_str().unwrap()).unwrap_err();
+        assert!(
+            error.starts_with("Function 'main' in '") && error.contains("calls 'deep.one'"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_import_cycle_is_rejected() {
+        let dir = scratch_dir("cycle");
+        write_file(&dir, "a.lang", "import \"b.lang\";\n");
+        write_file(&dir, "b.lang", "import \"a.lang\";\n");
+
+        let error = load_program(dir.join("a.lang").to_str().unwrap()).unwrap_err();
+        assert!(error.starts_with("Import cycle detected"), "{}", error);
+        assert!(error.contains("a.lang -> ") && error.contains("b.lang"));
+    }
+
+    #[test]
+    fn test_duplicate_definition_is_rejected() {
+        let dir = scratch_dir("duplicate");
+        write_file(
+            &dir,
+            "math.lang",
+            "add: function() -> i32 {\n    return 1;\n};\n\nadd: function() -> i32 {\n    return 2;\n};\n",
+        );
+        write_file(
+            &dir,
+            "main.lang",
+            "import \"math.lang\";\n\nmain: function() -> i32 {\n    return 0;\n};\n",
+        );
+
+        let error = load_program(dir.join("main.lang").to_str().unwrap()).unwrap_err();
+        assert!(
+            error.contains("Duplicate definition of function 'math.add'"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_namespace_collision_names_both_files() {
+        let dir = scratch_dir("collision");
+        write_file(
+            &dir,
+            "a/util.lang",
+            "one: function() -> i32 {\n    return 1;\n};\n",
+        );
+        write_file(
+            &dir,
+            "b/util.lang",
+            "two: function() -> i32 {\n    return 2;\n};\n",
+        );
+        write_file(
+            &dir,
+            "main.lang",
+            "import \"a/util.lang\";\nimport \"b/util.lang\";\n\nmain: function() -> i32 {\n    return 0;\n};\n",
+        );
+
+        let error = load_program(dir.join("main.lang").to_str().unwrap()).unwrap_err();
+        assert!(error.starts_with("Namespace 'util' of "), "{}", error);
+        let a = Path::new("a").join("util.lang");
+        let b = Path::new("b").join("util.lang");
+        assert!(
+            error.contains(&b.display().to_string()) && error.contains(&a.display().to_string()),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_missing_import_reports_path() {
+        let dir = scratch_dir("missing");
+        write_file(&dir, "main.lang", "import \"nope.lang\";\n");
+
+        let error = load_program(dir.join("main.lang").to_str().unwrap()).unwrap_err();
+        assert!(error.contains("nope.lang"), "{}", error);
+    }
+}
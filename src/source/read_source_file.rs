@@ -1,8 +1,8 @@
-///! Reads a source file from the filesystem and creates a SourceFile structure.
-///!
-///! This function validates the file path, reads the content, determines encoding,
-///! and calculates the file size. It handles common file I/O errors and ensures
-///! the file is readable as UTF-8 text.
+//! Reads a source file from the filesystem and creates a SourceFile structure.
+//!
+//! This function validates the file path, reads the content, determines encoding,
+//! and calculates the file size. It handles common file I/O errors and ensures
+//! the file is readable as UTF-8 text.
 
 /// Reads a source file from the given file path and returns a SourceFile structure.
 ///
@@ -46,7 +46,7 @@ fn detect_encoding(content: &str) -> std::string::String {
 
 /// Calculates the byte size of the content string.
 fn calculate_content_size(content: &str) -> u32 {
-    content.as_bytes().len() as u32
+    content.len() as u32
 }
 
 /// Structure representing a loaded source file.
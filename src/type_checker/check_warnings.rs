@@ -0,0 +1,438 @@
+//! Warning pass run alongside `type_check_program`.
+//!
+//! Reports code that is legal but probably a mistake: unused local variables
+//! and parameters, functions that can never be reached from `main`, and `if`
+//! conditions that do not depend on anything that can change. Names starting
+//! with `_` are exempt from the unused checks, as are functions from imported
+//! files, since a module may define more than one program uses.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::expression_struct::Expression;
+use crate::ast::function_struct::Function;
+use crate::ast::program_struct::Program;
+use crate::ast::statement_struct::Statement;
+use crate::ast::string_part_struct::StringPart;
+use crate::type_checker::type_error_struct::Location;
+use crate::type_checker::warning_struct::{Warning, WarningConfig, WarningId};
+
+/// Collects warnings for the program, applying the suppression and promotion
+/// rules from `config`. Promoted warnings have `Severity::Error`.
+pub fn check_warnings(program: &Program, config: &WarningConfig) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for function in &program.functions {
+        let mut checker = FunctionWarnings {
+            function,
+            config,
+            warnings: &mut warnings,
+            scopes: Vec::new(),
+            statement: None,
+            line: None,
+        };
+        checker.check_function();
+    }
+
+    for function in unreachable_functions(program) {
+        push_warning(
+            &mut warnings,
+            config,
+            WarningId::UnusedFunction,
+            format!("Function '{}' is never called from 'main'", function.name),
+            Location {
+                file: function.file.clone(),
+                line: Some(function.line),
+                function: function.name.clone(),
+                statement: None,
+            },
+        );
+    }
+
+    warnings
+}
+
+/// Adds a warning unless its ID is suppressed.
+fn push_warning(
+    warnings: &mut Vec<Warning>,
+    config: &WarningConfig,
+    id: WarningId,
+    message: String,
+    location: Location,
+) {
+    if let Some(severity) = config.severity(id) {
+        warnings.push(Warning {
+            id,
+            severity,
+            message,
+            location,
+        });
+    }
+}
+
+/// A declared parameter or local variable and whether it has been read.
+struct Binding {
+    name: String,
+    is_parameter: bool,
+    used: bool,
+    statement: Option<String>,
+    line: usize,
+}
+
+/// Walks one function, tracking variable usage per lexical scope.
+struct FunctionWarnings<'a> {
+    function: &'a Function,
+    config: &'a WarningConfig,
+    warnings: &'a mut Vec<Warning>,
+    scopes: Vec<Vec<Binding>>,
+    statement: Option<String>,
+    line: Option<usize>,
+}
+
+impl FunctionWarnings<'_> {
+    fn check_function(&mut self) {
+        let params = self
+            .function
+            .params
+            .iter()
+            .map(|param| Binding {
+                name: param.name.clone(),
+                is_parameter: true,
+                used: false,
+                statement: None,
+                line: self.function.line,
+            })
+            .collect();
+        self.scopes.push(params);
+        self.check_statements(&self.function.body);
+        self.pop_scope();
+    }
+
+    fn check_statements(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.statement = Some(stmt.describe());
+            self.line = Some(stmt.line());
+
+            match stmt {
+                Statement::VariableDeclaration { name, value, .. } => {
+                    self.visit_expression(value);
+                    let statement = self.statement.clone();
+                    if let Some(scope) = self.scopes.last_mut() {
+                        scope.push(Binding {
+                            name: name.clone(),
+                            is_parameter: false,
+                            used: false,
+                            statement,
+                            line: stmt.line(),
+                        });
+                    }
+                }
+                Statement::FunctionCall { args, .. } => {
+                    for arg in args {
+                        self.visit_expression(arg);
+                    }
+                }
+                Statement::If {
+                    condition,
+                    body,
+                    else_body,
+                    ..
+                } => {
+                    self.visit_expression(condition);
+                    if is_constant(condition) {
+                        self.push(
+                            WarningId::ConstantCondition,
+                            "If condition is constant, so the same branch is always taken"
+                                .to_string(),
+                            self.statement.clone(),
+                            self.line,
+                        );
+                    }
+
+                    self.scopes.push(Vec::new());
+                    self.check_statements(body);
+                    self.pop_scope();
+                    if let Some(else_statements) = else_body {
+                        self.scopes.push(Vec::new());
+                        self.check_statements(else_statements);
+                        self.pop_scope();
+                    }
+                }
+                Statement::Return { value, .. } => {
+                    if let Some(value) = value {
+                        self.visit_expression(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks every variable read by the expression as used.
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::IntegerLiteral(_) | Expression::StringLiteral(_) => {}
+            Expression::InterpolatedString(parts) => {
+                for part in parts {
+                    if let StringPart::Hole { expression, .. } = part {
+                        self.visit_expression(expression);
+                    }
+                }
+            }
+            Expression::VariableRef(name) => {
+                let binding = self
+                    .scopes
+                    .iter_mut()
+                    .rev()
+                    .find_map(|scope| scope.iter_mut().rev().find(|b| &b.name == name));
+                if let Some(binding) = binding {
+                    binding.used = true;
+                }
+            }
+            Expression::BinaryOp { left, right, .. } => {
+                self.visit_expression(left);
+                self.visit_expression(right);
+            }
+            Expression::FunctionCall { args, .. } => {
+                for arg in args {
+                    self.visit_expression(arg);
+                }
+            }
+        }
+    }
+
+    /// Closes the innermost scope and reports its unused bindings.
+    fn pop_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        for binding in scope {
+            if binding.used || binding.name.starts_with('_') {
+                continue;
+            }
+            if binding.is_parameter {
+                self.push(
+                    WarningId::UnusedParameter,
+                    format!("Parameter '{}' is never used", binding.name),
+                    None,
+                    Some(binding.line),
+                );
+            } else {
+                self.push(
+                    WarningId::UnusedVariable,
+                    format!("Variable '{}' is never used", binding.name),
+                    binding.statement,
+                    Some(binding.line),
+                );
+            }
+        }
+    }
+
+    fn push(
+        &mut self,
+        id: WarningId,
+        message: String,
+        statement: Option<String>,
+        line: Option<usize>,
+    ) {
+        let location = Location {
+            file: self.function.file.clone(),
+            line,
+            function: self.function.name.clone(),
+            statement,
+        };
+        push_warning(self.warnings, self.config, id, message, location);
+    }
+}
+
+/// Returns true if the expression only involves literals.
+fn is_constant(expr: &Expression) -> bool {
+    match expr {
+        Expression::IntegerLiteral(_) | Expression::StringLiteral(_) => true,
+        Expression::InterpolatedString(parts) => parts.iter().all(|part| match part {
+            StringPart::Literal(_) => true,
+            StringPart::Hole { expression, .. } => is_constant(expression),
+        }),
+        Expression::VariableRef(_) | Expression::FunctionCall { .. } => false,
+        Expression::BinaryOp { left, right, .. } => is_constant(left) && is_constant(right),
+    }
+}
+
+/// Returns the functions of the root file that cannot be reached from `main`.
+/// Nothing is reported for programs without a `main` function.
+fn unreachable_functions(program: &Program) -> Vec<&Function> {
+    let functions: HashMap<&str, &Function> = program
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f))
+        .collect();
+    if !functions.contains_key("main") {
+        return Vec::new();
+    }
+
+    let mut reachable: HashSet<&str> = HashSet::new();
+    let mut pending = vec!["main"];
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name) {
+            continue;
+        }
+        if let Some(function) = functions.get(name) {
+            let mut callees = Vec::new();
+            collect_calls(&function.body, &mut callees);
+            pending.extend(callees);
+        }
+    }
+
+    program
+        .functions
+        .iter()
+        .filter(|f| !reachable.contains(f.name.as_str()))
+        .filter(|f| !f.name.contains('.') && !f.name.starts_with('_'))
+        .collect()
+}
+
+/// Collects the names of all functions called in the statements.
+fn collect_calls<'a>(statements: &'a [Statement], callees: &mut Vec<&'a str>) {
+    for stmt in statements {
+        match stmt {
+            Statement::VariableDeclaration { value, .. } => {
+                collect_expression_calls(value, callees)
+            }
+            Statement::FunctionCall { name, args, .. } => {
+                callees.push(name);
+                for arg in args {
+                    collect_expression_calls(arg, callees);
+                }
+            }
+            Statement::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                collect_expression_calls(condition, callees);
+                collect_calls(body, callees);
+                if let Some(else_statements) = else_body {
+                    collect_calls(else_statements, callees);
+                }
+            }
+            Statement::Return { value, .. } => {
+                if let Some(value) = value {
+                    collect_expression_calls(value, callees);
+                }
+            }
+        }
+    }
+}
+
+fn collect_expression_calls<'a>(expr: &'a Expression, callees: &mut Vec<&'a str>) {
+    match expr {
+        Expression::IntegerLiteral(_)
+        | Expression::StringLiteral(_)
+        | Expression::VariableRef(_) => {}
+        Expression::InterpolatedString(parts) => {
+            for part in parts {
+                if let StringPart::Hole { expression, .. } = part {
+                    collect_expression_calls(expression, callees);
+                }
+            }
+        }
+        Expression::BinaryOp { left, right, .. } => {
+            collect_expression_calls(left, callees);
+            collect_expression_calls(right, callees);
+        }
+        Expression::FunctionCall { name, args } => {
+            callees.push(name);
+            for arg in args {
+                collect_expression_calls(arg, callees);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program::parse_program;
+    use crate::type_checker::warning_struct::Severity;
+
+    fn warning_codes(source: &str, config: &WarningConfig) -> Vec<(&'static str, String)> {
+        let program = parse_program(source).unwrap();
+        check_warnings(&program, config)
+            .into_iter()
+            .map(|w| (w.id.code(), w.message))
+            .collect()
+    }
+
+    const SOURCE: &str = r#"
+add_numbers: function(a: i32, b: i32) -> i32 {
+    return a;
+};
+
+unused_helper: function() -> i32 {
+    return 1;
+};
+
+main: function() -> i32 {
+    count: i32 = 42;
+    text: i32 = 42;
+    _ignored: i32 = 1;
+
+    if (1 > 0) {
+        inner: i32 = add_numbers(count, 8);
+    };
+
+    return 0;
+};
+"#;
+
+    #[test]
+    fn test_reports_unused_code_and_constant_conditions() {
+        let warnings = warning_codes(SOURCE, &WarningConfig::default());
+        assert_eq!(
+            warnings,
+            vec![
+                ("W002", "Parameter 'b' is never used".to_string()),
+                (
+                    "W004",
+                    "If condition is constant, so the same branch is always taken".to_string()
+                ),
+                ("W001", "Variable 'inner' is never used".to_string()),
+                ("W001", "Variable 'text' is never used".to_string()),
+                (
+                    "W003",
+                    "Function 'unused_helper' is never called from 'main'".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suppressed_and_promoted_warnings() {
+        let program = parse_program(SOURCE).unwrap();
+        let config = WarningConfig {
+            suppressed: [WarningId::UnusedVariable, WarningId::ConstantCondition]
+                .into_iter()
+                .collect(),
+            promoted: [WarningId::UnusedFunction].into_iter().collect(),
+        };
+
+        let warnings = check_warnings(&program, &config);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].id, WarningId::UnusedParameter);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(warnings[1].id, WarningId::UnusedFunction);
+        assert_eq!(warnings[1].severity, Severity::Error);
+        assert_eq!(
+            warnings[1].to_string(),
+            "error[W003]: Line 6: In function 'unused_helper': Function 'unused_helper' is never called from 'main'"
+        );
+    }
+
+    #[test]
+    fn test_warning_codes_round_trip() {
+        for id in WarningId::ALL {
+            assert_eq!(WarningId::from_code(id.code()), Some(id));
+        }
+        assert_eq!(WarningId::from_code("W999"), None);
+    }
+}
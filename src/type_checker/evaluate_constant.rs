@@ -0,0 +1,118 @@
+//! Compile-time evaluation of literal-only expressions.
+//!
+//! Follows the evaluator's runtime semantics for binary operators, so a folded
+//! value is exactly what the program would compute. Operations that would fail
+//! at runtime, such as overflow or division by zero, become type errors.
+
+use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::constant_struct::Constant;
+use crate::type_checker::type_error_struct::TypeErrorKind;
+
+/// Folds a binary operation whose operands may be constant.
+///
+/// Returns `Ok(None)` if the result is not known at compile time. Dividing by
+/// a constant zero is an error even when the left operand is not constant.
+pub fn evaluate_constant_op(
+    op: &BinaryOperator,
+    left: Option<&Constant>,
+    right: Option<&Constant>,
+) -> Result<Option<Constant>, TypeErrorKind> {
+    use BinaryOperator::*;
+    use Constant::*;
+
+    if *op == Divide && right == Some(&Int(0)) {
+        return Err(TypeErrorKind::DivisionByZero);
+    }
+
+    let (Some(left), Some(right)) = (left, right) else {
+        return Ok(None);
+    };
+
+    let result = match (left, right) {
+        (Int(l), Int(r)) => match op {
+            Add => l.checked_add(*r),
+            Subtract => l.checked_sub(*r),
+            Multiply => l.checked_mul(*r),
+            Divide => l.checked_div(*r),
+            GreaterThan => Some((l > r) as i32),
+            LessThan => Some((l < r) as i32),
+            Equal => Some((l == r) as i32),
+        },
+        // Strings only support equality at runtime; other comparisons are left unfolded
+        (Str(l), Str(r)) if *op == Equal => Some((l == r) as i32),
+        _ => return Ok(None),
+    };
+
+    match result {
+        Some(value) => Ok(Some(Int(value))),
+        None => Err(TypeErrorKind::ConstantOverflow {
+            op: op.clone(),
+            left: left.clone(),
+            right: right.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::binary_operator_struct::BinaryOperator::*;
+    use crate::ast::constant_struct::Constant::*;
+
+    #[test]
+    fn test_folds_integer_and_string_operations() {
+        assert_eq!(
+            evaluate_constant_op(&Add, Some(&Int(2)), Some(&Int(3))),
+            Ok(Some(Int(5)))
+        );
+        assert_eq!(
+            evaluate_constant_op(&Divide, Some(&Int(7)), Some(&Int(2))),
+            Ok(Some(Int(3)))
+        );
+        assert_eq!(
+            evaluate_constant_op(&LessThan, Some(&Int(1)), Some(&Int(2))),
+            Ok(Some(Int(1)))
+        );
+        assert_eq!(
+            evaluate_constant_op(
+                &Equal,
+                Some(&Str("a".to_string())),
+                Some(&Str("a".to_string()))
+            ),
+            Ok(Some(Int(1)))
+        );
+        assert_eq!(
+            evaluate_constant_op(
+                &LessThan,
+                Some(&Str("a".to_string())),
+                Some(&Str("b".to_string()))
+            ),
+            Ok(None)
+        );
+        assert_eq!(evaluate_constant_op(&Add, None, Some(&Int(1))), Ok(None));
+    }
+
+    #[test]
+    fn test_overflow_and_division_by_zero() {
+        assert_eq!(
+            evaluate_constant_op(&Add, Some(&Int(i32::MAX)), Some(&Int(1))),
+            Err(TypeErrorKind::ConstantOverflow {
+                op: Add,
+                left: Int(i32::MAX),
+                right: Int(1),
+            })
+        );
+        assert_eq!(
+            evaluate_constant_op(&Divide, Some(&Int(i32::MIN)), Some(&Int(-1))),
+            Err(TypeErrorKind::ConstantOverflow {
+                op: Divide,
+                left: Int(i32::MIN),
+                right: Int(-1),
+            })
+        );
+        assert_eq!(
+            evaluate_constant_op(&Divide, None, Some(&Int(0))),
+            Err(TypeErrorKind::DivisionByZero)
+        );
+    }
+}
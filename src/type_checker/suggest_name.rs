@@ -0,0 +1,67 @@
+//! Suggests known names for misspelled identifiers using edit distance.
+
+/// Returns the candidate closest to `name`, if it is close enough to be a
+/// plausible misspelling. Ties are broken alphabetically so suggestions are stable.
+pub fn suggest_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Computes the Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut current = vec![0; b_chars.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b_chars.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("print", "print"), 0);
+        assert_eq!(edit_distance("prnt", "print"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_suggest_closest_name() {
+        let names = ["print", "int_to_string", "add_numbers", "math.add"];
+        assert_eq!(suggest_name("pritn", names), Some("print".to_string()));
+        assert_eq!(
+            suggest_name("add_nubmers", names),
+            Some("add_numbers".to_string())
+        );
+        assert_eq!(suggest_name("math.ad", names), Some("math.add".to_string()));
+        assert_eq!(suggest_name("completely_different", names), None);
+    }
+
+    #[test]
+    fn test_ties_are_broken_alphabetically() {
+        assert_eq!(suggest_name("cat", ["bat", "hat"]), Some("bat".to_string()));
+        assert_eq!(suggest_name("cat", ["hat", "bat"]), Some("bat".to_string()));
+    }
+}
@@ -1,241 +1,718 @@
-///! Type-checks an entire program by verifying each function is correctly typed.
-///!
-///! This module validates that all functions in a program have consistent types,
-///! proper variable declarations, correct function calls, and valid control flow.
-///! Returns detailed error messages for any type mismatches found.
-///! Ensures type safety before program evaluation begins.
-///! Enhanced to support if-else statements with proper type checking.
+//! Type-checks an entire program by verifying each function is correctly typed.
+//!
+//! This module validates that all functions in a program have consistent types,
+//! proper variable declarations, correct function calls, and valid control flow.
+//! Checking continues after an error so that every problem is reported at once:
+//! an expression whose type could not be determined is "poisoned" and silently
+//! accepted wherever it is used, which avoids cascades of follow-up errors.
+//! Ensures type safety before program evaluation begins.
+//!
+//! A program that passes is returned as a `TypedProgram`, in which every
+//! expression carries its type, every variable reference is resolved to
+//! its declaration and every call to its callee.
 use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::callee_struct::Callee;
+use crate::ast::constant_struct::Constant;
 use crate::ast::expression_struct::Expression;
 use crate::ast::function_struct::Function;
 use crate::ast::program_struct::Program;
 use crate::ast::statement_struct::Statement;
+use crate::ast::string_part_struct::StringPart;
 use crate::ast::type_struct::Type;
+use crate::ast::typed_expression_struct::{TypedExpression, TypedExpressionKind};
+use crate::ast::typed_function_struct::TypedFunction;
+use crate::ast::typed_program_struct::TypedProgram;
+use crate::ast::typed_statement_struct::TypedStatement;
+use crate::ast::typed_string_part_struct::TypedStringPart;
+use crate::ast::variable_struct::{Variable, VariableId};
+use crate::builtins::builtin_registry::builtins;
+use crate::type_checker::evaluate_constant::evaluate_constant_op;
+use crate::type_checker::suggest_name::suggest_name;
+use crate::type_checker::type_error_struct::{Location, TypeError, TypeErrorKind};
+use std::collections::{HashMap, HashSet};
 
 /// Type-checks the entire program by verifying each function is correctly typed.
-/// Returns Ok(()) if all functions pass type checking, otherwise returns an error
-/// describing the first encountered type error.
-pub fn type_check_program(program: &Program) -> Result<(), String> {
+/// Returns the typed program if all functions pass type checking, otherwise
+/// returns every type error found, in program order.
+pub fn type_check_program(program: &Program) -> Result<TypedProgram, Vec<TypeError>> {
+    check(program, true)
+}
+
+/// Type-checks a program whose functions are called from Rust rather than
+/// run from `main`, e.g. by an `Interpreter`. Like `type_check_program`,
+/// except that the program does not need to define `main`; one that is
+/// defined must still be valid.
+pub fn type_check_library(program: &Program) -> Result<TypedProgram, Vec<TypeError>> {
+    check(program, false)
+}
+
+fn check(program: &Program, require_main: bool) -> Result<TypedProgram, Vec<TypeError>> {
+    // Collect every signature first so calls can be checked in any order
+    let signatures = build_signature_table(program);
+    let mut errors = Vec::new();
+    check_program_structure(program, require_main, &mut errors);
+
+    let mut functions = Vec::new();
     for function in &program.functions {
-        type_check_function(function)?;
+        let mut checker = FunctionChecker::new(function, &signatures, &mut errors);
+        functions.push(checker.check_function());
     }
-    Ok(())
+
+    if errors.is_empty() {
+        Ok(TypedProgram { functions })
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parameter and return types of a callable function, and where to find it.
+struct FunctionSignature {
+    params: Vec<Type>,
+    return_type: Type,
+    callee: Callee,
 }
 
-/// Type-checks a single function by validating parameters, body statements, and return type.
-fn type_check_function(function: &Function) -> Result<(), String> {
-    let mut context = TypeContext::new();
+/// Builds the table of builtin and user-defined function signatures.
+fn build_signature_table(program: &Program) -> HashMap<String, FunctionSignature> {
+    let mut signatures: HashMap<String, FunctionSignature> = builtins()
+        .iter()
+        .enumerate()
+        .map(|(index, builtin)| {
+            let signature = FunctionSignature {
+                params: builtin.params.to_vec(),
+                return_type: builtin.return_type.clone(),
+                callee: Callee::Builtin(index),
+            };
+            (builtin.name.to_string(), signature)
+        })
+        .collect();
 
-    // Add parameters to context
-    for param in &function.params {
-        if context.contains(&param.name) {
-            return Err(format!(
-                "Parameter '{}' declared multiple times",
-                param.name
+    // Duplicates are reported by `check_program_structure`; the first definition wins
+    for (index, function) in program.functions.iter().enumerate() {
+        signatures
+            .entry(function.name.clone())
+            .or_insert_with(|| FunctionSignature {
+                params: function
+                    .params
+                    .iter()
+                    .map(|p| p.param_type.clone())
+                    .collect(),
+                return_type: function.return_type.clone(),
+                callee: Callee::Function(index),
+            });
+    }
+    signatures
+}
+
+/// Checks program-level rules: function names are unique and do not redefine
+/// builtins, and there is exactly one `main` taking no parameters and returning i32.
+/// Without `require_main`, a missing `main` is accepted.
+fn check_program_structure(program: &Program, require_main: bool, errors: &mut Vec<TypeError>) {
+    // Builtins count as already defined, so user code cannot redefine them
+    let mut defined: HashSet<&str> = builtins().iter().map(|builtin| builtin.name).collect();
+    for function in &program.functions {
+        if !defined.insert(&function.name) {
+            errors.push(function_error(
+                function,
+                TypeErrorKind::DuplicateFunction {
+                    name: function.name.clone(),
+                },
             ));
         }
-        context.insert(param.name.clone(), param.param_type.clone());
     }
 
-    // Type check all statements in the function body
-    for stmt in &function.body {
-        type_check_statement(stmt, &mut context)?;
+    let Some(main) = program.functions.iter().find(|f| f.name == "main") else {
+        if require_main {
+            errors.push(program_error("main", TypeErrorKind::MissingMain));
+        }
+        return;
+    };
+    if !main.params.is_empty() {
+        errors.push(function_error(
+            main,
+            TypeErrorKind::MainParameters {
+                found: main.params.len(),
+            },
+        ));
     }
+    if main.return_type != Type::I32 {
+        errors.push(function_error(
+            main,
+            TypeErrorKind::MainReturnType {
+                found: main.return_type.clone(),
+            },
+        ));
+    }
+}
 
-    // Verify that non-void functions have a return statement
-    if function.return_type != Type::Void {
-        if let Some(last_stmt) = function.body.last() {
-            if !is_return_statement(last_stmt) {
-                return Err(format!(
-                    "Function '{}' missing return statement",
-                    function.name
-                ));
-            }
-        } else {
-            return Err(format!(
-                "Function '{}' has empty body but non-void return type",
-                function.name
-            ));
-        }
+fn program_error(function: &str, kind: TypeErrorKind) -> TypeError {
+    TypeError {
+        kind,
+        location: Location {
+            file: None,
+            line: None,
+            function: function.to_string(),
+            statement: None,
+        },
     }
+}
 
-    Ok(())
+/// Builds an error about a function as a whole, located at its header.
+fn function_error(function: &Function, kind: TypeErrorKind) -> TypeError {
+    TypeError {
+        kind,
+        location: Location {
+            file: function.file.clone(),
+            line: Some(function.line),
+            function: function.name.clone(),
+            statement: None,
+        },
+    }
 }
 
-/// Type-checks a statement within the given context and updates variable bindings.
-/// Enhanced to handle if-else statements properly.
-fn type_check_statement(stmt: &Statement, context: &mut TypeContext) -> Result<(), String> {
-    match stmt {
-        Statement::VariableDeclaration {
-            name,
-            var_type,
-            value,
-        } => {
-            let expr_type = type_check_expression(value, context)?;
-            if &expr_type != var_type {
-                return Err(format!(
-                    "Type mismatch for variable '{}': expected {:?}, found {:?}",
-                    name, var_type, expr_type
-                ));
+/// Type-checks one function, recording errors instead of stopping at the first.
+///
+/// Expression checks return `None` for a poisoned expression: its error has
+/// already been reported, so enclosing checks accept it without reporting more.
+struct FunctionChecker<'a> {
+    function: &'a Function,
+    signatures: &'a HashMap<String, FunctionSignature>,
+    errors: &'a mut Vec<TypeError>,
+    context: TypeContext,
+    /// Declarations of the function so far, indexed by `VariableId`.
+    variables: Vec<Variable>,
+    /// Description of the statement currently being checked, for error locations.
+    statement: Option<String>,
+    /// Source line of the statement currently being checked.
+    line: Option<usize>,
+}
+
+impl<'a> FunctionChecker<'a> {
+    fn new(
+        function: &'a Function,
+        signatures: &'a HashMap<String, FunctionSignature>,
+        errors: &'a mut Vec<TypeError>,
+    ) -> Self {
+        Self {
+            function,
+            signatures,
+            errors,
+            context: TypeContext::new(),
+            variables: Vec::new(),
+            statement: None,
+            line: None,
+        }
+    }
+
+    /// Records an error at the current location.
+    fn report(&mut self, kind: TypeErrorKind) {
+        self.errors.push(TypeError {
+            kind,
+            location: Location {
+                file: self.function.file.clone(),
+                line: self.line,
+                function: self.function.name.clone(),
+                statement: self.statement.clone(),
+            },
+        });
+    }
+
+    /// Allocates the next variable id of the function for a declaration.
+    fn declare_variable(&mut self, name: &str, var_type: &Type) -> VariableId {
+        let id = VariableId(self.variables.len());
+        self.variables.push(Variable {
+            name: name.to_string(),
+            var_type: var_type.clone(),
+        });
+        id
+    }
+
+    /// Type-checks the function's parameters, body statements, and return paths.
+    fn check_function(&mut self) -> TypedFunction {
+        let function = self.function;
+
+        // Add parameters to context
+        let mut params = Vec::new();
+        for param in &function.params {
+            let id = self.declare_variable(&param.name, &param.param_type);
+            params.push(id);
+            if self.context.contains(&param.name) {
+                self.report(TypeErrorKind::DuplicateParameter {
+                    name: param.name.clone(),
+                });
+                continue;
             }
-            if context.contains(name) {
-                return Err(format!("Variable '{}' redeclared in the same scope", name));
+            if param.param_type == Type::Void {
+                self.report(TypeErrorKind::VoidParameter {
+                    name: param.name.clone(),
+                });
+                self.context.insert(param.name.clone(), id, None);
+                continue;
             }
-            context.insert(name.clone(), var_type.clone());
-            Ok(())
+            self.context
+                .insert(param.name.clone(), id, Some(param.param_type.clone()));
         }
-        Statement::FunctionCall { name: _, args } => {
-            for arg in args {
-                type_check_expression(arg, context)?;
-            }
-            Ok(())
+
+        // Type check all statements in the function body
+        let body = function
+            .body
+            .iter()
+            .filter_map(|stmt| self.check_statement(stmt))
+            .collect();
+
+        // Verify that non-void functions return on every path
+        let always_returns = self.check_control_flow(&function.body);
+        if function.return_type != Type::Void && !always_returns {
+            self.statement = None;
+            self.line = Some(function.line);
+            self.report(TypeErrorKind::MissingReturn);
         }
-        Statement::If {
-            condition,
+
+        TypedFunction {
+            name: function.name.clone(),
+            params,
+            return_type: function.return_type.clone(),
+            variables: std::mem::take(&mut self.variables),
             body,
-            else_body,
-        } => {
-            let cond_type = type_check_expression(condition, context)?;
-            if cond_type != Type::I32 {
-                return Err(format!(
-                    "If condition must be of type i32 (interpreted as boolean), found {:?}",
-                    cond_type
-                ));
-            }
+            file: function.file.clone(),
+        }
+    }
 
-            // Type check if body statements
-            for stmt in body {
-                type_check_statement(stmt, context)?;
-            }
+    /// Type-checks a statement and updates variable bindings.
+    /// Returns `None` if the statement contains a poisoned expression.
+    fn check_statement(&mut self, stmt: &Statement) -> Option<TypedStatement> {
+        self.statement = Some(stmt.describe());
+        self.line = Some(stmt.line());
+        let line = stmt.line();
+
+        match stmt {
+            Statement::VariableDeclaration {
+                name,
+                var_type,
+                value,
+                ..
+            } => {
+                let value = self.check_expression(value);
+                let declared_type = if *var_type == Type::Void {
+                    self.report(TypeErrorKind::VoidVariable { name: name.clone() });
+                    None
+                } else {
+                    if let Some(value) = &value
+                        && &value.expr_type != var_type
+                    {
+                        self.report(TypeErrorKind::VariableTypeMismatch {
+                            name: name.clone(),
+                            expected: var_type.clone(),
+                            found: value.expr_type.clone(),
+                        });
+                    }
+                    // Later uses see the declared type even if the initializer was wrong
+                    Some(var_type.clone())
+                };
+
+                let variable = self.declare_variable(name, var_type);
+                if self.context.declared_in_current_scope(name) {
+                    self.report(TypeErrorKind::Redeclaration { name: name.clone() });
+                } else if self.context.contains(name) {
+                    self.report(TypeErrorKind::Shadowing { name: name.clone() });
+                } else {
+                    self.context.insert(name.clone(), variable, declared_type);
+                }
 
-            // Type check else body statements if they exist
-            if let Some(else_statements) = else_body {
-                for stmt in else_statements {
-                    type_check_statement(stmt, context)?;
+                Some(TypedStatement::VariableDeclaration {
+                    variable,
+                    value: value?,
+                    line,
+                })
+            }
+            Statement::FunctionCall { name, args, .. } => {
+                // A statement-level call discards its result, so only void calls are allowed
+                let call = self.check_call(name, args)?;
+                if call.expr_type != Type::Void {
+                    self.report(TypeErrorKind::IgnoredResult {
+                        function: name.clone(),
+                        return_type: call.expr_type,
+                    });
+                    return None;
+                }
+                match call.kind {
+                    TypedExpressionKind::FunctionCall { name, callee, args } => {
+                        Some(TypedStatement::FunctionCall {
+                            name,
+                            callee,
+                            args,
+                            line,
+                        })
+                    }
+                    _ => None,
                 }
             }
+            Statement::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                let condition = self.check_expression(condition);
+                if let Some(condition) = &condition
+                    && condition.expr_type != Type::I32
+                {
+                    self.report(TypeErrorKind::InvalidCondition {
+                        found: condition.expr_type.clone(),
+                    });
+                }
 
-            Ok(())
-        }
-        Statement::Return { value } => {
-            let _ = type_check_expression(value, context)?;
-            Ok(())
+                // Each branch is its own block scope
+                let body = self.check_block(body);
+                let else_body = else_body
+                    .as_ref()
+                    .map(|else_statements| self.check_block(else_statements));
+
+                Some(TypedStatement::If {
+                    condition: condition?,
+                    body,
+                    else_body,
+                    line,
+                })
+            }
+            Statement::Return { value, .. } => {
+                let return_type = &self.function.return_type;
+                match value {
+                    Some(value) => {
+                        let value = self.check_expression(value);
+                        if *return_type == Type::Void {
+                            self.report(TypeErrorKind::UnexpectedReturnValue);
+                        } else if let Some(value) = &value
+                            && &value.expr_type != return_type
+                        {
+                            self.report(TypeErrorKind::ReturnTypeMismatch {
+                                expected: return_type.clone(),
+                                found: value.expr_type.clone(),
+                            });
+                        }
+                        Some(TypedStatement::Return {
+                            value: Some(value?),
+                            line,
+                        })
+                    }
+                    None => {
+                        if *return_type != Type::Void {
+                            self.report(TypeErrorKind::MissingReturnValue {
+                                expected: return_type.clone(),
+                            });
+                        }
+                        Some(TypedStatement::Return { value: None, line })
+                    }
+                }
+            }
         }
     }
-}
 
-/// Type-checks an expression and returns its type.
-fn type_check_expression(expr: &Expression, context: &TypeContext) -> Result<Type, String> {
-    match expr {
-        Expression::IntegerLiteral(_) => Ok(Type::I32),
-        Expression::StringLiteral(_) => Ok(Type::String),
-        Expression::VariableRef(name) => context
-            .get(name)
-            .cloned()
-            .ok_or_else(|| format!("Use of undeclared variable '{}'", name)),
-        Expression::BinaryOp { op, left, right } => {
-            let left_type = type_check_expression(left, context)?;
-            let right_type = type_check_expression(right, context)?;
-            check_binary_op_types(op, &left_type, &right_type)
-        }
-        Expression::FunctionCall { name, args } => {
-            // Handle built-in functions
-            match name.as_str() {
-                "print" => {
-                    if args.len() != 1 {
-                        return Err(String::from("print expects exactly one argument"));
-                    }
-                    let arg_type = type_check_expression(&args[0], context)?;
-                    if arg_type != Type::String {
-                        return Err(String::from("print expects a string argument"));
+    /// Type-checks a block of statements in a fresh scope; its declarations end with it.
+    fn check_block(&mut self, statements: &[Statement]) -> Vec<TypedStatement> {
+        self.context.push_scope();
+        let typed = statements
+            .iter()
+            .filter_map(|stmt| self.check_statement(stmt))
+            .collect();
+        self.context.pop_scope();
+        typed
+    }
+
+    /// Type-checks an expression and returns it with its resolved type, or
+    /// `None` if it is poisoned.
+    fn check_expression(&mut self, expr: &Expression) -> Option<TypedExpression> {
+        let (kind, expr_type) = match expr {
+            Expression::IntegerLiteral(value) => {
+                (TypedExpressionKind::IntegerLiteral(*value), Type::I32)
+            }
+            Expression::StringLiteral(value) => (
+                TypedExpressionKind::StringLiteral(value.clone()),
+                Type::String,
+            ),
+            Expression::InterpolatedString(parts) => {
+                // No implicit conversions: every hole must already be a string.
+                // A wrong hole is reported but the result is still a string.
+                let mut typed_parts = Vec::new();
+                let mut index = 0;
+                for part in parts {
+                    match part {
+                        StringPart::Literal(text) => {
+                            typed_parts.push(TypedStringPart::Literal(text.clone()))
+                        }
+                        StringPart::Hole { source, expression } => {
+                            index += 1;
+                            match self.check_expression(expression) {
+                                Some(hole) if hole.expr_type != Type::String => {
+                                    self.report(TypeErrorKind::InterpolationHoleType {
+                                        index,
+                                        source: source.clone(),
+                                        found: hole.expr_type,
+                                    });
+                                }
+                                Some(hole) => typed_parts.push(TypedStringPart::Hole(hole)),
+                                None => {}
+                            }
+                        }
                     }
-                    Ok(Type::Void)
                 }
-                "int_to_string" => {
-                    if args.len() != 1 {
-                        return Err(String::from("int_to_string expects exactly one argument"));
+                (
+                    TypedExpressionKind::InterpolatedString(typed_parts),
+                    Type::String,
+                )
+            }
+            Expression::VariableRef(name) => match self.context.get(name) {
+                Some(binding) => (
+                    TypedExpressionKind::Variable(binding.variable),
+                    binding.var_type.clone()?,
+                ),
+                None => {
+                    self.report(TypeErrorKind::UndeclaredVariable { name: name.clone() });
+                    return None;
+                }
+            },
+            Expression::BinaryOp { op, left, right } => {
+                let left = self.check_expression(left);
+                let right = self.check_expression(right);
+                let (left, right) = (left?, right?);
+                let Some(result_type) =
+                    check_binary_op_types(op, &left.expr_type, &right.expr_type)
+                else {
+                    self.report(TypeErrorKind::InvalidOperands {
+                        op: op.clone(),
+                        left: left.expr_type,
+                        right: right.expr_type,
+                    });
+                    return None;
+                };
+                let kind = TypedExpressionKind::BinaryOp {
+                    op: op.clone(),
+                    left: Box::new(left),
+                    right: Box::new(right),
+                };
+                (kind, result_type)
+            }
+            Expression::FunctionCall { name, args } => return self.check_call(name, args),
+        };
+
+        let constant = self.fold_constant(&kind);
+        Some(TypedExpression {
+            kind,
+            expr_type,
+            constant,
+        })
+    }
+
+    /// Computes the compile-time value of an expression from its operands'
+    /// values, reporting operations that would fail at runtime.
+    fn fold_constant(&mut self, kind: &TypedExpressionKind) -> Option<Constant> {
+        match kind {
+            TypedExpressionKind::IntegerLiteral(value) => Some(Constant::Int(*value)),
+            TypedExpressionKind::StringLiteral(value) => Some(Constant::Str(value.clone())),
+            TypedExpressionKind::InterpolatedString(parts) => {
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        TypedStringPart::Literal(literal) => text.push_str(literal),
+                        TypedStringPart::Hole(TypedExpression {
+                            constant: Some(Constant::Str(value)),
+                            ..
+                        }) => text.push_str(value),
+                        TypedStringPart::Hole(_) => return None,
                     }
-                    let arg_type = type_check_expression(&args[0], context)?;
-                    if arg_type != Type::I32 {
-                        return Err(String::from("int_to_string expects an i32 argument"));
+                }
+                Some(Constant::Str(text))
+            }
+            TypedExpressionKind::BinaryOp { op, left, right } => {
+                match evaluate_constant_op(op, left.constant.as_ref(), right.constant.as_ref()) {
+                    Ok(constant) => constant,
+                    Err(kind) => {
+                        self.report(kind);
+                        None
                     }
-                    Ok(Type::String)
                 }
-                _ => {
-                    // For user-defined functions, we'd need function signature lookup
-                    // For now, assume they return i32
-                    for arg in args {
-                        type_check_expression(arg, context)?;
+            }
+            TypedExpressionKind::Variable(_) | TypedExpressionKind::FunctionCall { .. } => None,
+        }
+    }
+
+    /// Checks a call against the callee's signature and returns the typed call.
+    /// Used for calls in expressions as well as statement-level calls.
+    fn check_call(&mut self, name: &str, args: &[Expression]) -> Option<TypedExpression> {
+        let signatures = self.signatures;
+        let typed_args: Vec<Option<TypedExpression>> =
+            args.iter().map(|arg| self.check_expression(arg)).collect();
+
+        // Every callee must resolve to a builtin or user-defined function
+        let Some(signature) = signatures.get(name) else {
+            let suggestion = suggest_name(name, signatures.keys().map(String::as_str));
+            self.report(TypeErrorKind::UndefinedFunction {
+                name: name.to_string(),
+                suggestion,
+            });
+            return None;
+        };
+
+        let mut valid = args.len() == signature.params.len();
+        if !valid {
+            self.report(TypeErrorKind::ArgumentCount {
+                function: name.to_string(),
+                expected: signature.params.len(),
+                found: args.len(),
+            });
+        } else {
+            for (index, (arg, param_type)) in typed_args.iter().zip(&signature.params).enumerate() {
+                match arg.as_ref().map(|arg| &arg.expr_type) {
+                    Some(Type::Void) => self.report(TypeErrorKind::VoidValue {
+                        usage: format!("an argument to '{}'", name),
+                    }),
+                    Some(arg_type) if arg_type != param_type => {
+                        self.report(TypeErrorKind::ArgumentTypeMismatch {
+                            function: name.to_string(),
+                            index: index + 1,
+                            expected: param_type.clone(),
+                            found: arg_type.clone(),
+                        })
                     }
-                    Ok(Type::I32)
+                    Some(_) => continue,
+                    None => {}
                 }
+                valid = false;
             }
         }
+
+        // The return type is known even if the arguments were wrong. An invalid
+        // call keeps no arguments; its errors are already reported, so it never
+        // reaches a returned `TypedProgram`.
+        let args = match typed_args.into_iter().collect::<Option<Vec<_>>>() {
+            Some(args) if valid => args,
+            _ => Vec::new(),
+        };
+        let kind = TypedExpressionKind::FunctionCall {
+            name: name.to_string(),
+            callee: signature.callee,
+            args,
+        };
+        Some(TypedExpression {
+            kind,
+            expr_type: signature.return_type.clone(),
+            constant: None,
+        })
+    }
+
+    /// Returns true if every path through the statements ends in a `return`.
+    ///
+    /// An `if` returns on every path only when both of its branches do. The
+    /// first statement following a point where every path has already returned
+    /// is reported as unreachable.
+    fn check_control_flow(&mut self, statements: &[Statement]) -> bool {
+        let mut always_returns = false;
+
+        for stmt in statements {
+            if always_returns {
+                self.statement = None;
+                self.line = Some(stmt.line());
+                self.report(TypeErrorKind::UnreachableStatement {
+                    statement: stmt.describe(),
+                });
+                break;
+            }
+
+            always_returns = match stmt {
+                Statement::Return { .. } => true,
+                Statement::If {
+                    body, else_body, ..
+                } => {
+                    let body_returns = self.check_control_flow(body);
+                    let else_returns = match else_body {
+                        Some(else_statements) => self.check_control_flow(else_statements),
+                        None => false,
+                    };
+                    body_returns && else_returns
+                }
+                Statement::VariableDeclaration { .. } | Statement::FunctionCall { .. } => false,
+            };
+        }
+
+        always_returns
     }
 }
 
 /// Checks if binary operation is valid for given types and returns result type.
-fn check_binary_op_types(op: &BinaryOperator, left: &Type, right: &Type) -> Result<Type, String> {
+fn check_binary_op_types(op: &BinaryOperator, left: &Type, right: &Type) -> Option<Type> {
     use BinaryOperator::*;
     use Type::*;
 
     match op {
-        Add | Subtract | Multiply | Divide => {
-            if left == &I32 && right == &I32 {
-                Ok(I32)
-            } else {
-                Err(format!(
-                    "Arithmetic operator '{:?}' requires both operands to be i32, got {:?} and {:?}",
-                    op, left, right
-                ))
-            }
-        }
+        Add | Subtract | Multiply | Divide => (left == &I32 && right == &I32).then_some(I32),
         GreaterThan | LessThan | Equal => {
-            if left == right && (*left == I32 || *left == String) {
-                Ok(I32) // Boolean result as i32
-            } else {
-                Err(format!(
-                    "Comparison operator '{:?}' requires both operands to be same type (i32 or string), got {:?} and {:?}",
-                    op, left, right
-                ))
-            }
+            // Boolean result as i32
+            (left == right && (*left == I32 || *left == String)).then_some(I32)
         }
     }
 }
 
-/// Returns true if the statement is a return statement.
-fn is_return_statement(stmt: &Statement) -> bool {
-    matches!(stmt, Statement::Return { .. })
+/// Type context tracking variable types in a stack of lexical scopes.
+///
+/// Shadowing rule: a declaration may not reuse the name of any variable that
+/// is still visible (including parameters). Once a block ends its names are
+/// free again, so sibling blocks such as `if` and `else` may reuse them.
+struct TypeContext {
+    scopes: Vec<HashMap<String, Binding>>,
 }
 
-/// Simple type context for tracking variable types in current scope.
-struct TypeContext {
-    variables: std::collections::HashMap<String, Type>,
+/// A visible variable: its declaration and type, or `None` if the type is poisoned.
+struct Binding {
+    variable: VariableId,
+    var_type: Option<Type>,
 }
 
 impl TypeContext {
     fn new() -> Self {
         Self {
-            variables: std::collections::HashMap::new(),
+            scopes: vec![HashMap::new()],
         }
     }
 
-    fn insert(&mut self, name: String, ty: Type) {
-        self.variables.insert(name, ty);
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn insert(&mut self, name: String, variable: VariableId, var_type: Option<Type>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, Binding { variable, var_type });
+        }
     }
 
+    /// Returns true if the variable is visible from the current scope.
     fn contains(&self, name: &str) -> bool {
-        self.variables.contains_key(name)
+        self.get(name).is_some()
+    }
+
+    fn declared_in_current_scope(&self, name: &str) -> bool {
+        self.scopes
+            .last()
+            .is_some_and(|scope| scope.contains_key(name))
     }
 
-    fn get(&self, name: &str) -> Option<&Type> {
-        self.variables.get(name)
+    fn get(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::parameter_struct::Parameter;
+
+    /// Type-checks a program that is expected to fail and returns its first error message.
+    fn first_error(program: &Program) -> String {
+        type_check_program(program).unwrap_err()[0].to_string()
+    }
 
     #[test]
     fn test_type_mismatch_i32_string() {
@@ -249,21 +726,41 @@ mod tests {
                         name: "count".to_string(),
                         var_type: Type::I32,
                         value: Expression::StringLiteral("test".to_string()),
+                        line: 2,
                     },
                     Statement::Return {
-                        value: Expression::IntegerLiteral(0),
+                        value: Some(Expression::IntegerLiteral(0)),
+                        line: 3,
                     },
                 ],
+                line: 1,
+                file: Some("main.lang".to_string()),
             }],
+            imports: vec![],
         };
 
-        let result = type_check_program(&program);
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .contains("Type mismatch for variable 'count'")
+        let errors = type_check_program(&program).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            TypeErrorKind::VariableTypeMismatch {
+                name: "count".to_string(),
+                expected: Type::I32,
+                found: Type::String,
+            }
+        );
+        assert_eq!(
+            errors[0].location,
+            Location {
+                file: Some("main.lang".to_string()),
+                line: Some(2),
+                function: "main".to_string(),
+                statement: Some("declaration of 'count'".to_string()),
+            }
         );
+        assert!(errors[0].to_string().starts_with(
+            "main.lang:2: In function 'main', declaration of 'count': Type mismatch for variable 'count'"
+        ));
     }
 
     #[test]
@@ -278,18 +775,1001 @@ mod tests {
                         name: "count".to_string(),
                         var_type: Type::I32,
                         value: Expression::IntegerLiteral(42),
+                        line: 1,
                     },
                     Statement::Return {
-                        value: Expression::VariableRef("count".to_string()),
+                        value: Some(Expression::VariableRef("count".to_string())),
+                        line: 1,
                     },
                 ],
+                line: 1,
+                file: None,
             }],
+            imports: vec![],
         };
 
         let result = type_check_program(&program);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_ignored_non_void_call_result() {
+        let program = Program {
+            functions: vec![
+                Function {
+                    name: "add_numbers".to_string(),
+                    params: vec![],
+                    return_type: Type::I32,
+                    body: vec![Statement::Return {
+                        value: Some(Expression::IntegerLiteral(3)),
+                        line: 1,
+                    }],
+                    line: 1,
+                    file: None,
+                },
+                Function {
+                    name: "main".to_string(),
+                    params: vec![],
+                    return_type: Type::I32,
+                    body: vec![
+                        Statement::FunctionCall {
+                            name: "add_numbers".to_string(),
+                            args: vec![],
+                            line: 1,
+                        },
+                        Statement::Return {
+                            value: Some(Expression::IntegerLiteral(0)),
+                            line: 1,
+                        },
+                    ],
+                    line: 1,
+                    file: None,
+                },
+            ],
+            imports: vec![],
+        };
+
+        assert!(first_error(&program).contains("Result of call to 'add_numbers' is ignored"));
+    }
+
+    /// Builds a program with a single void function `log` and a trivial `main`.
+    fn program_with_void_function(body: Vec<Statement>) -> Program {
+        Program {
+            functions: vec![
+                Function {
+                    name: "log".to_string(),
+                    params: vec![],
+                    return_type: Type::Void,
+                    body,
+                    line: 1,
+                    file: None,
+                },
+                Function {
+                    name: "main".to_string(),
+                    params: vec![],
+                    return_type: Type::I32,
+                    body: vec![Statement::Return {
+                        value: Some(Expression::IntegerLiteral(0)),
+                        line: 1,
+                    }],
+                    line: 1,
+                    file: None,
+                },
+            ],
+            imports: vec![],
+        }
+    }
+
+    #[test]
+    fn test_void_function_with_implicit_and_bare_return() {
+        let print_hello = Statement::FunctionCall {
+            name: "print".to_string(),
+            args: vec![Expression::StringLiteral("hello".to_string())],
+            line: 1,
+        };
+        assert!(type_check_program(&program_with_void_function(vec![])).is_ok());
+        assert!(type_check_program(&program_with_void_function(vec![print_hello])).is_ok());
+        assert!(
+            type_check_program(&program_with_void_function(vec![Statement::Return {
+                value: None,
+                line: 1,
+            }]))
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_void_function_cannot_return_a_value() {
+        let program = program_with_void_function(vec![Statement::Return {
+            value: Some(Expression::IntegerLiteral(1)),
+            line: 1,
+        }]);
+        let error = first_error(&program);
+        assert!(error.contains("returns void and cannot return a value"));
+    }
+
+    #[test]
+    fn test_bare_return_in_non_void_function() {
+        let program = Program {
+            functions: vec![Function {
+                name: "main".to_string(),
+                params: vec![],
+                return_type: Type::I32,
+                body: vec![Statement::Return {
+                    value: None,
+                    line: 1,
+                }],
+                line: 1,
+                file: None,
+            }],
+            imports: vec![],
+        };
+        let error = first_error(&program);
+        assert!(error.contains("must return a value of type I32"));
+    }
+
+    #[test]
+    fn test_void_value_used_in_expression() {
+        let mut program = program_with_void_function(vec![]);
+        program.functions[1].body.insert(
+            0,
+            Statement::VariableDeclaration {
+                name: "x".to_string(),
+                var_type: Type::I32,
+                value: Expression::BinaryOp {
+                    op: BinaryOperator::Add,
+                    left: Box::new(Expression::FunctionCall {
+                        name: "print".to_string(),
+                        args: vec![Expression::StringLiteral("a".to_string())],
+                    }),
+                    right: Box::new(Expression::IntegerLiteral(1)),
+                },
+                line: 1,
+            },
+        );
+        assert!(type_check_program(&program).is_err());
+
+        let mut program = program_with_void_function(vec![]);
+        program.functions[1].body.insert(
+            0,
+            Statement::VariableDeclaration {
+                name: "x".to_string(),
+                var_type: Type::Void,
+                value: Expression::FunctionCall {
+                    name: "log".to_string(),
+                    args: vec![],
+                },
+                line: 1,
+            },
+        );
+        let error = first_error(&program);
+        assert!(error.contains("Variable 'x' cannot have type void"));
+    }
+
+    /// Builds `main` with `count: i32 = 1;`, the given if statement and `return 0;`.
+    fn program_with_if(if_statement: Statement, after: Vec<Statement>) -> Program {
+        let mut body = vec![
+            Statement::VariableDeclaration {
+                name: "count".to_string(),
+                var_type: Type::I32,
+                value: Expression::IntegerLiteral(1),
+                line: 1,
+            },
+            if_statement,
+        ];
+        body.extend(after);
+        body.push(Statement::Return {
+            value: Some(Expression::IntegerLiteral(0)),
+            line: 1,
+        });
+
+        Program {
+            functions: vec![Function {
+                name: "main".to_string(),
+                params: vec![],
+                return_type: Type::I32,
+                body,
+                line: 1,
+                file: None,
+            }],
+            imports: vec![],
+        }
+    }
+
+    fn declare(name: &str, value: i32) -> Statement {
+        Statement::VariableDeclaration {
+            name: name.to_string(),
+            var_type: Type::I32,
+            value: Expression::IntegerLiteral(value),
+            line: 1,
+        }
+    }
+
+    #[test]
+    fn test_same_name_in_both_branches() {
+        let program = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![declare("value", 1)],
+                else_body: Some(vec![declare("value", 2)]),
+                line: 1,
+            },
+            vec![],
+        );
+        assert!(type_check_program(&program).is_ok());
+    }
+
+    #[test]
+    fn test_block_variable_does_not_leak() {
+        let program = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![declare("value", 1)],
+                else_body: None,
+                line: 1,
+            },
+            vec![Statement::VariableDeclaration {
+                name: "copy".to_string(),
+                var_type: Type::I32,
+                value: Expression::VariableRef("value".to_string()),
+                line: 1,
+            }],
+        );
+        let error = first_error(&program);
+        assert!(error.contains("Use of undeclared variable 'value'"));
+
+        // The name is free again after the block ends
+        let program = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![declare("value", 1)],
+                else_body: None,
+                line: 1,
+            },
+            vec![declare("value", 2)],
+        );
+        assert!(type_check_program(&program).is_ok());
+    }
+
+    #[test]
+    fn test_shadowing_outer_variable_is_rejected() {
+        let program = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![declare("count", 2)],
+                else_body: None,
+                line: 1,
+            },
+            vec![],
+        );
+        let error = first_error(&program);
+        assert!(error.contains("Variable 'count' shadows a variable from an enclosing scope"));
+    }
+
+    fn interpolate(holes: Vec<Expression>) -> Expression {
+        Expression::InterpolatedString(
+            holes
+                .into_iter()
+                .enumerate()
+                .map(|(index, expression)| StringPart::Hole {
+                    source: format!("hole_{}", index),
+                    expression,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_interpolation_holes_must_be_strings() {
+        let converted = Expression::FunctionCall {
+            name: "int_to_string".to_string(),
+            args: vec![Expression::VariableRef("count".to_string())],
+        };
+        let valid = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![Statement::FunctionCall {
+                    name: "print".to_string(),
+                    args: vec![interpolate(vec![converted])],
+                    line: 1,
+                }],
+                else_body: None,
+                line: 1,
+            },
+            vec![],
+        );
+        assert!(type_check_program(&valid).is_ok());
+
+        let invalid = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![Statement::FunctionCall {
+                    name: "print".to_string(),
+                    args: vec![interpolate(vec![
+                        Expression::StringLiteral("ok".to_string()),
+                        Expression::VariableRef("count".to_string()),
+                    ])],
+                    line: 1,
+                }],
+                else_body: None,
+                line: 1,
+            },
+            vec![],
+        );
+        let error = first_error(&invalid);
+        assert!(
+            error.contains("Interpolation hole 2 {hole_1} must be a string, found I32"),
+            "{}",
+            error
+        );
+    }
+
+    /// Builds `add_numbers(a: i32, b: i32) -> i32` and a `main` running `statement`.
+    fn program_calling_add_numbers(statement: Statement) -> Program {
+        Program {
+            functions: vec![
+                Function {
+                    name: "add_numbers".to_string(),
+                    params: vec![
+                        Parameter {
+                            name: "a".to_string(),
+                            param_type: Type::I32,
+                        },
+                        Parameter {
+                            name: "b".to_string(),
+                            param_type: Type::I32,
+                        },
+                    ],
+                    return_type: Type::I32,
+                    body: vec![Statement::Return {
+                        value: Some(Expression::BinaryOp {
+                            op: BinaryOperator::Add,
+                            left: Box::new(Expression::VariableRef("a".to_string())),
+                            right: Box::new(Expression::VariableRef("b".to_string())),
+                        }),
+                        line: 1,
+                    }],
+                    line: 1,
+                    file: None,
+                },
+                Function {
+                    name: "main".to_string(),
+                    params: vec![],
+                    return_type: Type::I32,
+                    body: vec![
+                        statement,
+                        Statement::Return {
+                            value: Some(Expression::IntegerLiteral(0)),
+                            line: 1,
+                        },
+                    ],
+                    line: 1,
+                    file: None,
+                },
+            ],
+            imports: vec![],
+        }
+    }
+
+    fn call_add_numbers(args: Vec<Expression>) -> Expression {
+        Expression::FunctionCall {
+            name: "add_numbers".to_string(),
+            args,
+        }
+    }
+
+    #[test]
+    fn test_call_checked_against_signature() {
+        let valid = program_calling_add_numbers(Statement::VariableDeclaration {
+            name: "sum".to_string(),
+            var_type: Type::I32,
+            value: call_add_numbers(vec![
+                Expression::IntegerLiteral(1),
+                Expression::IntegerLiteral(2),
+            ]),
+            line: 1,
+        });
+        assert!(type_check_program(&valid).is_ok());
+
+        let wrong_arg = program_calling_add_numbers(Statement::VariableDeclaration {
+            name: "s".to_string(),
+            var_type: Type::String,
+            value: call_add_numbers(vec![
+                Expression::StringLiteral("x".to_string()),
+                Expression::IntegerLiteral(2),
+            ]),
+            line: 1,
+        });
+        let error = first_error(&wrong_arg);
+        assert!(
+            error.contains("Argument 1 of call to 'add_numbers' expects I32, found String"),
+            "{}",
+            error
+        );
+
+        let wrong_return = program_calling_add_numbers(Statement::VariableDeclaration {
+            name: "s".to_string(),
+            var_type: Type::String,
+            value: call_add_numbers(vec![
+                Expression::IntegerLiteral(1),
+                Expression::IntegerLiteral(2),
+            ]),
+            line: 1,
+        });
+        let error = first_error(&wrong_return);
+        assert!(error.contains("Type mismatch for variable 's': expected String, found I32"));
+    }
+
+    #[test]
+    fn test_call_arity_is_checked_in_statements() {
+        let program = program_calling_add_numbers(Statement::FunctionCall {
+            name: "print".to_string(),
+            args: vec![],
+            line: 1,
+        });
+        let error = first_error(&program);
+        assert!(error.contains("Function 'print' expects 1 argument(s), found 0"));
+
+        let program = program_calling_add_numbers(Statement::VariableDeclaration {
+            name: "sum".to_string(),
+            var_type: Type::I32,
+            value: call_add_numbers(vec![Expression::IntegerLiteral(1)]),
+            line: 1,
+        });
+        let error = first_error(&program);
+        assert!(error.contains("Function 'add_numbers' expects 2 argument(s), found 1"));
+    }
+
+    #[test]
+    fn test_return_value_checked_against_return_type() {
+        let program = Program {
+            functions: vec![
+                Function {
+                    name: "f".to_string(),
+                    params: vec![],
+                    return_type: Type::String,
+                    body: vec![Statement::Return {
+                        value: Some(Expression::IntegerLiteral(1)),
+                        line: 1,
+                    }],
+                    line: 1,
+                    file: None,
+                },
+                Function {
+                    name: "main".to_string(),
+                    params: vec![],
+                    return_type: Type::I32,
+                    body: vec![Statement::Return {
+                        value: Some(Expression::IntegerLiteral(0)),
+                        line: 1,
+                    }],
+                    line: 1,
+                    file: None,
+                },
+            ],
+            imports: vec![],
+        };
+        let error = first_error(&program);
+        assert_eq!(
+            error,
+            "Line 1: In function 'f', return statement: Return type mismatch: expected String, found I32"
+        );
+    }
+
+    #[test]
+    fn test_nested_return_checked_against_return_type() {
+        let program = program_with_if(
+            Statement::If {
+                condition: Expression::VariableRef("count".to_string()),
+                body: vec![Statement::Return {
+                    value: Some(Expression::StringLiteral("early".to_string())),
+                    line: 1,
+                }],
+                else_body: None,
+                line: 1,
+            },
+            vec![],
+        );
+        let error = first_error(&program);
+        assert!(error.contains(
+            "In function 'main', return statement: Return type mismatch: expected I32, found String"
+        ));
+    }
+
+    fn return_value(value: i32) -> Statement {
+        Statement::Return {
+            value: Some(Expression::IntegerLiteral(value)),
+            line: 1,
+        }
+    }
+
+    /// Builds `main() -> i32` declaring `flag: i32 = 1;` followed by the given body.
+    fn main_with_body(body: Vec<Statement>) -> Program {
+        let mut statements = vec![declare("flag", 1)];
+        statements.extend(body);
+        Program {
+            functions: vec![Function {
+                name: "main".to_string(),
+                params: vec![],
+                return_type: Type::I32,
+                body: statements,
+                line: 1,
+                file: None,
+            }],
+            imports: vec![],
+        }
+    }
+
+    #[test]
+    fn test_if_else_returning_on_both_branches() {
+        let program = main_with_body(vec![Statement::If {
+            condition: Expression::VariableRef("flag".to_string()),
+            body: vec![return_value(1)],
+            else_body: Some(vec![Statement::If {
+                condition: Expression::VariableRef("flag".to_string()),
+                body: vec![return_value(2)],
+                else_body: Some(vec![return_value(3)]),
+                line: 1,
+            }]),
+            line: 1,
+        }]);
+        assert!(type_check_program(&program).is_ok());
+    }
+
+    #[test]
+    fn test_missing_return_on_some_path() {
+        let program = main_with_body(vec![Statement::If {
+            condition: Expression::VariableRef("flag".to_string()),
+            body: vec![return_value(1)],
+            else_body: None,
+            line: 1,
+        }]);
+        let error = first_error(&program);
+        assert_eq!(
+            error,
+            "Line 1: In function 'main': Function does not return a value on every path"
+        );
+
+        let error = first_error(&main_with_body(vec![]));
+        assert_eq!(
+            error,
+            "Line 1: In function 'main': Function does not return a value on every path"
+        );
+    }
+
+    #[test]
+    fn test_unreachable_statement_after_return() {
+        let program = main_with_body(vec![return_value(0), declare("late", 1)]);
+        let error = first_error(&program);
+        assert_eq!(
+            error,
+            "Line 1: In function 'main': Unreachable declaration of 'late' after return"
+        );
+
+        let program = main_with_body(vec![
+            Statement::If {
+                condition: Expression::VariableRef("flag".to_string()),
+                body: vec![return_value(1)],
+                else_body: Some(vec![return_value(2)]),
+                line: 1,
+            },
+            return_value(3),
+        ]);
+        let error = first_error(&program);
+        assert_eq!(
+            error,
+            "Line 1: In function 'main': Unreachable return statement after return"
+        );
+    }
+
+    #[test]
+    fn test_all_errors_are_collected() {
+        let program = main_with_body(vec![
+            Statement::VariableDeclaration {
+                name: "a".to_string(),
+                var_type: Type::I32,
+                value: Expression::StringLiteral("x".to_string()),
+                line: 1,
+            },
+            Statement::VariableDeclaration {
+                name: "b".to_string(),
+                var_type: Type::String,
+                value: Expression::IntegerLiteral(1),
+                line: 1,
+            },
+            Statement::FunctionCall {
+                name: "print".to_string(),
+                args: vec![Expression::IntegerLiteral(2)],
+                line: 1,
+            },
+            return_value(0),
+        ]);
+
+        let errors = type_check_program(&program).unwrap_err();
+        let kinds: Vec<&TypeErrorKind> = errors.iter().map(|e| &e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &TypeErrorKind::VariableTypeMismatch {
+                    name: "a".to_string(),
+                    expected: Type::I32,
+                    found: Type::String,
+                },
+                &TypeErrorKind::VariableTypeMismatch {
+                    name: "b".to_string(),
+                    expected: Type::String,
+                    found: Type::I32,
+                },
+                &TypeErrorKind::ArgumentTypeMismatch {
+                    function: "print".to_string(),
+                    index: 1,
+                    expected: Type::String,
+                    found: Type::I32,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_poisoned_expressions_do_not_cascade() {
+        // `missing` is undeclared; neither the addition, the declaration of
+        // `total`, nor the later use of `total` should add further errors.
+        let program = main_with_body(vec![
+            Statement::VariableDeclaration {
+                name: "total".to_string(),
+                var_type: Type::I32,
+                value: Expression::BinaryOp {
+                    op: BinaryOperator::Add,
+                    left: Box::new(Expression::VariableRef("missing".to_string())),
+                    right: Box::new(Expression::IntegerLiteral(1)),
+                },
+                line: 1,
+            },
+            Statement::FunctionCall {
+                name: "print".to_string(),
+                args: vec![Expression::FunctionCall {
+                    name: "int_to_string".to_string(),
+                    args: vec![Expression::VariableRef("total".to_string())],
+                }],
+                line: 1,
+            },
+            Statement::FunctionCall {
+                name: "print".to_string(),
+                args: vec![Expression::FunctionCall {
+                    name: "undefined".to_string(),
+                    args: vec![],
+                }],
+                line: 1,
+            },
+            return_value(0),
+        ]);
+
+        let errors = type_check_program(&program).unwrap_err();
+        let kinds: Vec<&TypeErrorKind> = errors.iter().map(|e| &e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &TypeErrorKind::UndeclaredVariable {
+                    name: "missing".to_string(),
+                },
+                &TypeErrorKind::UndefinedFunction {
+                    name: "undefined".to_string(),
+                    suggestion: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undefined_function_with_suggestion() {
+        let program = program_calling_add_numbers(Statement::VariableDeclaration {
+            name: "sum".to_string(),
+            var_type: Type::I32,
+            value: Expression::FunctionCall {
+                name: "add_nubmers".to_string(),
+                args: vec![Expression::IntegerLiteral(1), Expression::IntegerLiteral(2)],
+            },
+            line: 1,
+        });
+        assert_eq!(
+            first_error(&program),
+            "Line 1: In function 'main', declaration of 'sum': Call to undefined function 'add_nubmers'; did you mean 'add_numbers'?"
+        );
+
+        // Statement-level calls are resolved too, including builtins
+        let program = program_calling_add_numbers(Statement::FunctionCall {
+            name: "pritn".to_string(),
+            args: vec![Expression::StringLiteral("hi".to_string())],
+            line: 1,
+        });
+        let errors = type_check_program(&program).unwrap_err();
+        assert_eq!(
+            errors[0].kind,
+            TypeErrorKind::UndefinedFunction {
+                name: "pritn".to_string(),
+                suggestion: Some("print".to_string()),
+            }
+        );
+    }
+
+    fn function(name: &str, params: Vec<Parameter>, return_type: Type) -> Function {
+        let body = match return_type {
+            Type::Void => vec![],
+            _ => vec![return_value(0)],
+        };
+        Function {
+            name: name.to_string(),
+            params,
+            return_type,
+            body,
+            line: 1,
+            file: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_functions_are_rejected() {
+        let program = Program {
+            functions: vec![
+                function("helper", vec![], Type::I32),
+                function("helper", vec![], Type::I32),
+                function("print", vec![], Type::Void),
+                function("main", vec![], Type::I32),
+                function("main", vec![], Type::I32),
+            ],
+            imports: vec![],
+        };
+
+        let errors: Vec<String> = type_check_program(&program)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Line 1: In function 'helper': Function 'helper' is already defined",
+                "Line 1: In function 'print': Function 'print' is already defined",
+                "Line 1: In function 'main': Function 'main' is already defined",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_main_is_required_and_validated() {
+        let program = Program {
+            functions: vec![function("helper", vec![], Type::I32)],
+            imports: vec![],
+        };
+        assert_eq!(
+            first_error(&program),
+            "In function 'main': Program must define a 'main' function"
+        );
+
+        let program = Program {
+            functions: vec![function(
+                "main",
+                vec![Parameter {
+                    name: "argc".to_string(),
+                    param_type: Type::I32,
+                }],
+                Type::String,
+            )],
+            imports: vec![],
+        };
+        let kinds: Vec<TypeErrorKind> = type_check_program(&program)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(
+            kinds[..2],
+            [
+                TypeErrorKind::MainParameters { found: 1 },
+                TypeErrorKind::MainReturnType {
+                    found: Type::String
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_library_does_not_require_main() {
+        let program = Program {
+            functions: vec![function("helper", vec![], Type::I32)],
+            imports: vec![],
+        };
+        assert_eq!(type_check_library(&program).unwrap().functions.len(), 1);
+
+        // A `main` that is defined is still validated
+        let program = Program {
+            functions: vec![function("main", vec![], Type::String)],
+            imports: vec![],
+        };
+        assert_eq!(
+            type_check_library(&program).unwrap_err()[0].kind,
+            TypeErrorKind::MainReturnType {
+                found: Type::String
+            }
+        );
+    }
+
+    #[test]
+    fn test_typed_program_resolves_types_and_variables() {
+        let program = crate::parser::parse_program::parse_program(
+            r#"
+main: function() -> i32 {
+    count: i32 = 1;
+    if (count > 0) {
+        label: string = "positive";
+        print(label);
+    } else {
+        label: string = "other";
+        print(label);
+    };
+    return count;
+};
+"#,
+        )
+        .unwrap();
+
+        let typed = type_check_program(&program).unwrap();
+        let main = &typed.functions[0];
+        let names: Vec<&str> = main.variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["count", "label", "label"]);
+
+        let TypedStatement::If {
+            condition,
+            body,
+            else_body: Some(else_body),
+            ..
+        } = &main.body[1]
+        else {
+            panic!("Expected an if/else statement");
+        };
+        assert_eq!(condition.expr_type, Type::I32);
+        let TypedExpressionKind::BinaryOp { left, .. } = &condition.kind else {
+            panic!("Expected a comparison");
+        };
+        assert_eq!(left.kind, TypedExpressionKind::Variable(VariableId(0)));
+
+        // Each branch refers to its own `label` declaration
+        let printed = |statement: &TypedStatement| match statement {
+            TypedStatement::FunctionCall { args, .. } => args[0].clone(),
+            _ => panic!("Expected a call to print"),
+        };
+        let then_label = printed(&body[1]);
+        assert_eq!(
+            then_label.kind,
+            TypedExpressionKind::Variable(VariableId(1))
+        );
+        assert_eq!(then_label.expr_type, Type::String);
+        assert_eq!(
+            printed(&else_body[1]).kind,
+            TypedExpressionKind::Variable(VariableId(2))
+        );
+    }
+
+    #[test]
+    fn test_calls_resolve_to_their_callee() {
+        let program = crate::parser::parse_program::parse_program(
+            r#"
+answer: function() -> i32 {
+    return 42;
+};
+
+main: function() -> i32 {
+    print("calling");
+    return answer();
+};
+"#,
+        )
+        .unwrap();
+
+        let typed = type_check_program(&program).unwrap();
+        let print_index = builtins().iter().position(|b| b.name == "print").unwrap();
+        let main = &typed.functions[typed.find_function("main").unwrap()];
+        assert!(matches!(
+            &main.body[0],
+            TypedStatement::FunctionCall { callee, .. } if *callee == Callee::Builtin(print_index)
+        ));
+        assert!(matches!(
+            &main.body[1],
+            TypedStatement::Return {
+                value: Some(TypedExpression {
+                    kind: TypedExpressionKind::FunctionCall {
+                        callee: Callee::Function(0),
+                        ..
+                    },
+                    ..
+                }),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_constant_expressions_are_folded() {
+        let program = crate::parser::parse_program::parse_program(
+            r#"
+main: function() -> i32 {
+    total: i32 = 2 * 3 + 4;
+    label: string = "total {int_to_string(total)}";
+    title: string = "answer";
+    return total;
+};
+"#,
+        )
+        .unwrap();
+
+        let typed = type_check_program(&program).unwrap();
+        let constants: Vec<Option<Constant>> = typed.functions[0]
+            .body
+            .iter()
+            .filter_map(|statement| match statement {
+                TypedStatement::VariableDeclaration { value, .. } => Some(value.constant.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            constants,
+            vec![
+                Some(Constant::Int(10)),
+                None,
+                Some(Constant::Str("answer".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constant_overflow_and_division_by_zero() {
+        let binary = |op, left, right| Expression::BinaryOp {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+        let program = main_with_body(vec![
+            Statement::VariableDeclaration {
+                name: "count".to_string(),
+                var_type: Type::I32,
+                value: binary(
+                    BinaryOperator::Add,
+                    Expression::IntegerLiteral(i32::MAX),
+                    Expression::IntegerLiteral(1),
+                ),
+                line: 1,
+            },
+            Statement::VariableDeclaration {
+                name: "ratio".to_string(),
+                var_type: Type::I32,
+                value: binary(
+                    BinaryOperator::Divide,
+                    Expression::VariableRef("flag".to_string()),
+                    binary(
+                        BinaryOperator::Subtract,
+                        Expression::IntegerLiteral(1),
+                        Expression::IntegerLiteral(1),
+                    ),
+                ),
+                line: 1,
+            },
+            return_value(0),
+        ]);
+
+        let errors: Vec<String> = type_check_program(&program)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Line 1: In function 'main', declaration of 'count': Constant expression overflows i32: Add of 2147483647 and 1",
+                "Line 1: In function 'main', declaration of 'ratio': Division by zero in constant expression",
+            ]
+        );
+    }
+
     #[test]
     fn test_if_else_type_checking() {
         let program = Program {
@@ -302,6 +1782,7 @@ mod tests {
                         name: "count".to_string(),
                         var_type: Type::I32,
                         value: Expression::IntegerLiteral(42),
+                        line: 1,
                     },
                     Statement::If {
                         condition: Expression::BinaryOp {
@@ -312,17 +1793,24 @@ mod tests {
                         body: vec![Statement::FunctionCall {
                             name: "print".to_string(),
                             args: vec![Expression::StringLiteral("Positive".to_string())],
+                            line: 1,
                         }],
                         else_body: Some(vec![Statement::FunctionCall {
                             name: "print".to_string(),
                             args: vec![Expression::StringLiteral("Not positive".to_string())],
+                            line: 1,
                         }]),
+                        line: 1,
                     },
                     Statement::Return {
-                        value: Expression::IntegerLiteral(0),
+                        value: Some(Expression::IntegerLiteral(0)),
+                        line: 1,
                     },
                 ],
+                line: 1,
+                file: None,
             }],
+            imports: vec![],
         };
 
         let result = type_check_program(&program);
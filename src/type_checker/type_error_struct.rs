@@ -0,0 +1,273 @@
+//! Structured type errors reported by the type checker.
+
+use std::fmt;
+
+use crate::ast::binary_operator_struct::BinaryOperator;
+use crate::ast::constant_struct::Constant;
+use crate::ast::type_struct::Type;
+
+/// A single type error with its kind and where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub kind: TypeErrorKind,
+    pub location: Location,
+}
+
+/// Where in the program a type error was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// Path of the source file, if the program was loaded from a file.
+    pub file: Option<String>,
+    /// Source line of the innermost enclosing statement, or of the function
+    /// header for errors about the function as a whole.
+    pub line: Option<usize>,
+    /// Name of the function containing the error.
+    pub function: String,
+    /// Description of the innermost enclosing statement, e.g. `declaration of 'x'`.
+    pub statement: Option<String>,
+}
+
+/// The kinds of type errors, carrying the names and types involved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeErrorKind {
+    DuplicateParameter {
+        name: String,
+    },
+    VoidParameter {
+        name: String,
+    },
+    VoidVariable {
+        name: String,
+    },
+    VariableTypeMismatch {
+        name: String,
+        expected: Type,
+        found: Type,
+    },
+    Redeclaration {
+        name: String,
+    },
+    Shadowing {
+        name: String,
+    },
+    IgnoredResult {
+        function: String,
+        return_type: Type,
+    },
+    InvalidCondition {
+        found: Type,
+    },
+    UnexpectedReturnValue,
+    MissingReturnValue {
+        expected: Type,
+    },
+    ReturnTypeMismatch {
+        expected: Type,
+        found: Type,
+    },
+    MissingReturn,
+    UnreachableStatement {
+        statement: String,
+    },
+    InterpolationHoleType {
+        index: usize,
+        source: String,
+        found: Type,
+    },
+    UndeclaredVariable {
+        name: String,
+    },
+    InvalidOperands {
+        op: BinaryOperator,
+        left: Type,
+        right: Type,
+    },
+    UndefinedFunction {
+        name: String,
+        /// Closest known function name, if any is a plausible misspelling.
+        suggestion: Option<String>,
+    },
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    ArgumentTypeMismatch {
+        function: String,
+        index: usize,
+        expected: Type,
+        found: Type,
+    },
+    VoidValue {
+        usage: String,
+    },
+    DuplicateFunction {
+        name: String,
+    },
+    MissingMain,
+    MainParameters {
+        found: usize,
+    },
+    MainReturnType {
+        found: Type,
+    },
+    ConstantOverflow {
+        op: BinaryOperator,
+        left: Constant,
+        right: Constant,
+    },
+    DivisionByZero,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+/// Formats as `file:line: In function 'name', statement`, leaving out
+/// whatever is not known.
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: ", file, line)?,
+            (Some(file), None) => write!(f, "{}: ", file)?,
+            (None, Some(line)) => write!(f, "Line {}: ", line)?,
+            (None, None) => {}
+        }
+        write!(f, "In function '{}'", self.function)?;
+        if let Some(statement) = &self.statement {
+            write!(f, ", {}", statement)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TypeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TypeErrorKind::*;
+
+        match self {
+            DuplicateParameter { name } => {
+                write!(f, "Parameter '{}' declared multiple times", name)
+            }
+            VoidParameter { name } => write!(f, "Parameter '{}' cannot have type void", name),
+            VoidVariable { name } => write!(f, "Variable '{}' cannot have type void", name),
+            VariableTypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Type mismatch for variable '{}': expected {:?}, found {:?}",
+                name, expected, found
+            ),
+            Redeclaration { name } => {
+                write!(f, "Variable '{}' redeclared in the same scope", name)
+            }
+            Shadowing { name } => write!(
+                f,
+                "Variable '{}' shadows a variable from an enclosing scope",
+                name
+            ),
+            IgnoredResult {
+                function,
+                return_type,
+            } => write!(
+                f,
+                "Result of call to '{}' is ignored: it returns {:?}; assign it to a variable",
+                function, return_type
+            ),
+            InvalidCondition { found } => write!(
+                f,
+                "If condition must be of type i32 (interpreted as boolean), found {:?}",
+                found
+            ),
+            UnexpectedReturnValue => write!(f, "Function returns void and cannot return a value"),
+            MissingReturnValue { expected } => {
+                write!(f, "Function must return a value of type {:?}", expected)
+            }
+            ReturnTypeMismatch { expected, found } => write!(
+                f,
+                "Return type mismatch: expected {:?}, found {:?}",
+                expected, found
+            ),
+            MissingReturn => write!(f, "Function does not return a value on every path"),
+            UnreachableStatement { statement } => {
+                write!(f, "Unreachable {} after return", statement)
+            }
+            InterpolationHoleType {
+                index,
+                source,
+                found,
+            } => write!(
+                f,
+                "Interpolation hole {} {{{}}} must be a string, found {:?}; convert it explicitly (e.g. int_to_string)",
+                index, source, found
+            ),
+            UndeclaredVariable { name } => write!(f, "Use of undeclared variable '{}'", name),
+            InvalidOperands { op, left, right } => match op {
+                BinaryOperator::Add
+                | BinaryOperator::Subtract
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide => write!(
+                    f,
+                    "Arithmetic operator '{:?}' requires both operands to be i32, got {:?} and {:?}",
+                    op, left, right
+                ),
+                BinaryOperator::GreaterThan | BinaryOperator::LessThan | BinaryOperator::Equal => {
+                    write!(
+                        f,
+                        "Comparison operator '{:?}' requires both operands to be same type (i32 or string), got {:?} and {:?}",
+                        op, left, right
+                    )
+                }
+            },
+            UndefinedFunction { name, suggestion } => {
+                write!(f, "Call to undefined function '{}'", name)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, "; did you mean '{}'?", suggestion)?;
+                }
+                Ok(())
+            }
+            ArgumentCount {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Function '{}' expects {} argument(s), found {}",
+                function, expected, found
+            ),
+            ArgumentTypeMismatch {
+                function,
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Argument {} of call to '{}' expects {:?}, found {:?}",
+                index, function, expected, found
+            ),
+            VoidValue { usage } => write!(f, "Void value cannot be used as {}", usage),
+            DuplicateFunction { name } => {
+                write!(f, "Function '{}' is already defined", name)
+            }
+            MissingMain => write!(f, "Program must define a 'main' function"),
+            MainParameters { found } => write!(
+                f,
+                "Function 'main' must not take parameters, found {}",
+                found
+            ),
+            MainReturnType { found } => {
+                write!(f, "Function 'main' must return i32, found {:?}", found)
+            }
+            ConstantOverflow { op, left, right } => write!(
+                f,
+                "Constant expression overflows i32: {:?} of {} and {}",
+                op, left, right
+            ),
+            DivisionByZero => write!(f, "Division by zero in constant expression"),
+        }
+    }
+}
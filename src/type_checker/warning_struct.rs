@@ -0,0 +1,96 @@
+//! Warning diagnostics and the configuration that suppresses or promotes them.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::type_checker::type_error_struct::Location;
+
+/// A single warning found by `check_warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub id: WarningId,
+    pub severity: Severity,
+    pub message: String,
+    pub location: Location,
+}
+
+/// Identifies a kind of warning so it can be suppressed or promoted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningId {
+    /// A local variable is declared but never read.
+    UnusedVariable,
+    /// A parameter is never read in the function body.
+    UnusedParameter,
+    /// A function can never be reached from `main`.
+    UnusedFunction,
+    /// An `if` condition does not depend on any variable or call.
+    ConstantCondition,
+}
+
+/// Whether a diagnostic is reported as a warning or promoted to an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Selects which warnings are suppressed and which are promoted to errors.
+#[derive(Debug, Clone, Default)]
+pub struct WarningConfig {
+    pub suppressed: HashSet<WarningId>,
+    pub promoted: HashSet<WarningId>,
+}
+
+impl WarningId {
+    pub const ALL: [WarningId; 4] = [
+        WarningId::UnusedVariable,
+        WarningId::UnusedParameter,
+        WarningId::UnusedFunction,
+        WarningId::ConstantCondition,
+    ];
+
+    /// Stable code used to refer to the warning, e.g. `W001`.
+    pub fn code(self) -> &'static str {
+        match self {
+            WarningId::UnusedVariable => "W001",
+            WarningId::UnusedParameter => "W002",
+            WarningId::UnusedFunction => "W003",
+            WarningId::ConstantCondition => "W004",
+        }
+    }
+
+    /// Looks up a warning by its code, e.g. when reading a configuration.
+    pub fn from_code(code: &str) -> Option<WarningId> {
+        WarningId::ALL.into_iter().find(|id| id.code() == code)
+    }
+}
+
+impl WarningConfig {
+    /// Returns the severity for a warning, or `None` if it is suppressed.
+    pub fn severity(&self, id: WarningId) -> Option<Severity> {
+        if self.suppressed.contains(&id) {
+            None
+        } else if self.promoted.contains(&id) {
+            Some(Severity::Error)
+        } else {
+            Some(Severity::Warning)
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            label,
+            self.id.code(),
+            self.location,
+            self.message
+        )
+    }
+}
@@ -0,0 +1,46 @@
+use crate::evaluator::value::Value;
+use crate::vm::instruction_struct::Instruction;
+
+/// A program compiled by `compile_program`, ready to run on the stack VM.
+#[derive(Debug)]
+pub struct BytecodeProgram {
+    /// Literal values referenced by `Instruction::Constant`.
+    pub constants: Vec<Value>,
+    pub functions: Vec<CompiledFunction>,
+    /// Index of the `main` function in `functions`.
+    pub main: usize,
+}
+
+/// The bytecode of one function.
+///
+/// Every parameter and local variable has its own slot in the frame, numbered
+/// by its `VariableId`, so the parameters occupy the first slots and receive
+/// the arguments in order.
+#[derive(Debug)]
+pub struct CompiledFunction {
+    pub name: String,
+    /// Parameter names, used to show the arguments in backtraces.
+    pub params: Vec<String>,
+    /// Number of slots in a frame of this function, including the parameters.
+    pub locals: usize,
+    /// Whether the function returns a value rather than void.
+    pub returns_value: bool,
+    pub code: Vec<Instruction>,
+    /// Offset of the first instruction, source line and description of
+    /// every statement, in code order.
+    pub statements: Vec<(usize, usize, String)>,
+    /// Path of the file the function was loaded from, used in backtraces.
+    pub file: Option<String>,
+}
+
+impl CompiledFunction {
+    /// Returns the line and description of the innermost statement that contains
+    /// the instruction at `offset`, i.e. the last one starting at or before it.
+    pub fn statement_at(&self, offset: usize) -> Option<(usize, &str)> {
+        self.statements
+            .iter()
+            .rev()
+            .find(|(start, _, _)| *start <= offset)
+            .map(|(_, line, description)| (*line, description.as_str()))
+    }
+}
@@ -0,0 +1,355 @@
+//! Compiles a type-checked program to bytecode for the stack VM.
+//!
+//! Variables are addressed by slot rather than by name, calls by function
+//! index, and literals and folded constant expressions are loaded from a
+//! shared constant pool, so running the program does no name lookups.
+
+use crate::ast::callee_struct::Callee;
+use crate::ast::constant_struct::Constant;
+use crate::ast::type_struct::Type;
+use crate::ast::typed_expression_struct::{TypedExpression, TypedExpressionKind};
+use crate::ast::typed_function_struct::TypedFunction;
+use crate::ast::typed_program_struct::TypedProgram;
+use crate::ast::typed_statement_struct::TypedStatement;
+use crate::ast::typed_string_part_struct::TypedStringPart;
+use crate::builtins::builtin_registry::builtins;
+use crate::evaluator::value::Value;
+use crate::vm::bytecode_program_struct::{BytecodeProgram, CompiledFunction};
+use crate::vm::instruction_struct::Instruction;
+
+/// Compiles every function of the program.
+/// Fails if the program has no `main` function, which cannot happen for the
+/// output of `type_check_program`.
+pub fn compile_program(program: &TypedProgram) -> Result<BytecodeProgram, String> {
+    let main = program
+        .find_function("main")
+        .ok_or_else(|| "main function not found".to_string())?;
+
+    let mut constants = Vec::new();
+    let mut functions = Vec::new();
+    for (index, function) in program.functions.iter().enumerate() {
+        let compiler = FunctionCompiler {
+            program,
+            index,
+            function,
+            constants: &mut constants,
+            code: Vec::new(),
+            statements: Vec::new(),
+        };
+        functions.push(compiler.compile()?);
+    }
+
+    Ok(BytecodeProgram {
+        constants,
+        functions,
+        main,
+    })
+}
+
+/// Emits the code of one function.
+struct FunctionCompiler<'a> {
+    program: &'a TypedProgram,
+    /// Index of `function` in the program.
+    index: usize,
+    function: &'a TypedFunction,
+    constants: &'a mut Vec<Value>,
+    code: Vec<Instruction>,
+    statements: Vec<(usize, usize, String)>,
+}
+
+impl FunctionCompiler<'_> {
+    fn compile(mut self) -> Result<CompiledFunction, String> {
+        self.compile_statements(&self.function.body)?;
+        // Reached by void functions without a trailing `return`
+        self.code.push(Instruction::ReturnVoid);
+
+        Ok(CompiledFunction {
+            name: self.function.name.clone(),
+            params: self
+                .function
+                .params
+                .iter()
+                .map(|param| self.function.variables[param.0].name.clone())
+                .collect(),
+            locals: self.function.variables.len(),
+            returns_value: self.function.return_type != Type::Void,
+            code: self.code,
+            statements: self.statements,
+            file: self.function.file.clone(),
+        })
+    }
+
+    fn compile_statements(&mut self, statements: &[TypedStatement]) -> Result<(), String> {
+        for stmt in statements {
+            self.statements.push((
+                self.code.len(),
+                stmt.line(),
+                stmt.describe(&self.function.variables),
+            ));
+            self.compile_statement(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn compile_statement(&mut self, stmt: &TypedStatement) -> Result<(), String> {
+        match stmt {
+            TypedStatement::VariableDeclaration {
+                variable, value, ..
+            } => {
+                self.compile_expression(value)?;
+                self.code.push(Instruction::StoreLocal(variable.0));
+            }
+
+            TypedStatement::FunctionCall { callee, args, .. } => {
+                if self.compile_call(*callee, args)? != Type::Void {
+                    self.code.push(Instruction::Pop);
+                }
+            }
+
+            TypedStatement::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                self.compile_expression(condition)?;
+                let skip_body = self.emit_jump(Instruction::JumpIfFalse(0));
+                self.compile_statements(body)?;
+
+                if let Some(else_statements) = else_body {
+                    let skip_else = self.emit_jump(Instruction::Jump(0));
+                    self.patch_jump(skip_body);
+                    self.compile_statements(else_statements)?;
+                    self.patch_jump(skip_else);
+                } else {
+                    self.patch_jump(skip_body);
+                }
+            }
+
+            TypedStatement::Return {
+                value:
+                    Some(TypedExpression {
+                        kind: TypedExpressionKind::FunctionCall { callee, args, .. },
+                        constant: None,
+                        ..
+                    }),
+                ..
+            } if *callee == Callee::Function(self.index) => {
+                for arg in args {
+                    self.compile_expression(arg)?;
+                }
+                self.code.push(Instruction::TailCall);
+            }
+
+            TypedStatement::Return { value, .. } => match value {
+                Some(value) => {
+                    self.compile_expression(value)?;
+                    self.code.push(Instruction::Return);
+                }
+                None => self.code.push(Instruction::ReturnVoid),
+            },
+        }
+        Ok(())
+    }
+
+    /// Emits code that pushes the value of the expression.
+    fn compile_expression(&mut self, expr: &TypedExpression) -> Result<(), String> {
+        if let Some(constant) = &expr.constant {
+            let value = match constant {
+                Constant::Int(value) => Value::Int(*value),
+                Constant::Str(text) => Value::Str(text.clone()),
+            };
+            self.emit_constant(value);
+            return Ok(());
+        }
+
+        match &expr.kind {
+            TypedExpressionKind::IntegerLiteral(value) => self.emit_constant(Value::Int(*value)),
+            TypedExpressionKind::StringLiteral(text) => {
+                self.emit_constant(Value::Str(text.clone()))
+            }
+
+            TypedExpressionKind::InterpolatedString(parts) => {
+                for part in parts {
+                    match part {
+                        TypedStringPart::Literal(text) => {
+                            self.emit_constant(Value::Str(text.clone()))
+                        }
+                        TypedStringPart::Hole(expression) => self.compile_expression(expression)?,
+                    }
+                }
+                self.code.push(Instruction::Concat(parts.len()));
+            }
+
+            TypedExpressionKind::Variable(variable) => {
+                self.code.push(Instruction::LoadLocal(variable.0));
+            }
+
+            TypedExpressionKind::BinaryOp { op, left, right } => {
+                self.compile_expression(left)?;
+                self.compile_expression(right)?;
+                self.code.push(Instruction::BinaryOp(op.clone()));
+            }
+
+            TypedExpressionKind::FunctionCall { name, callee, args } => {
+                if self.compile_call(*callee, args)? == Type::Void {
+                    return Err(format!("Function '{}' did not return a value", name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Emits the arguments and the call, returning the callee's return type.
+    fn compile_call(&mut self, callee: Callee, args: &[TypedExpression]) -> Result<Type, String> {
+        for arg in args {
+            self.compile_expression(arg)?;
+        }
+
+        match callee {
+            Callee::Builtin(index) => {
+                self.code.push(Instruction::CallBuiltin(index));
+                Ok(builtins()[index].return_type.clone())
+            }
+            Callee::Function(index) => {
+                self.code.push(Instruction::Call(index));
+                Ok(self.program.functions[index].return_type.clone())
+            }
+        }
+    }
+
+    /// Emits a load of the value, adding it to the constant pool unless it is already there.
+    fn emit_constant(&mut self, value: Value) {
+        let index = match self
+            .constants
+            .iter()
+            .position(|constant| *constant == value)
+        {
+            Some(index) => index,
+            None => {
+                self.constants.push(value);
+                self.constants.len() - 1
+            }
+        };
+        self.code.push(Instruction::Constant(index));
+    }
+
+    /// Emits a jump whose target is filled in later by `patch_jump`.
+    fn emit_jump(&mut self, jump: Instruction) -> usize {
+        self.code.push(jump);
+        self.code.len() - 1
+    }
+
+    /// Points the jump at `offset` to the next instruction to be emitted.
+    fn patch_jump(&mut self, offset: usize) {
+        let next = self.code.len();
+        if let Instruction::Jump(target) | Instruction::JumpIfFalse(target) = &mut self.code[offset]
+        {
+            *target = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::binary_operator_struct::BinaryOperator;
+    use crate::parser::parse_program::parse_program;
+    use crate::type_checker::type_check_program::type_check_program;
+
+    fn compile(source: &str) -> BytecodeProgram {
+        let program = parse_program(source).unwrap();
+        compile_program(&type_check_program(&program).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_compiles_slots_jumps_and_constants() {
+        let bytecode = compile(
+            r#"
+main: function() -> i32 {
+    count: i32 = 2 * 3;
+    if (count > 5) {
+        print("big");
+    } else {
+        print("small");
+    };
+    return count;
+};
+"#,
+        );
+
+        use Instruction::*;
+        let main = &bytecode.functions[bytecode.main];
+        assert_eq!(
+            main.code,
+            vec![
+                Constant(0),
+                StoreLocal(0),
+                LoadLocal(0),
+                Constant(1),
+                BinaryOp(BinaryOperator::GreaterThan),
+                JumpIfFalse(9),
+                Constant(2),
+                CallBuiltin(0),
+                Jump(11),
+                Constant(3),
+                CallBuiltin(0),
+                LoadLocal(0),
+                Return,
+                ReturnVoid,
+            ]
+        );
+        assert_eq!(
+            bytecode.constants,
+            vec![
+                Value::Int(6),
+                Value::Int(5),
+                Value::Str("big".to_string()),
+                Value::Str("small".to_string()),
+            ]
+        );
+        assert_eq!(main.locals, 1);
+        assert_eq!(main.statement_at(4), Some((4, "if statement")));
+        assert_eq!(main.statement_at(7), Some((5, "call to 'print'")));
+        assert_eq!(main.statement_at(12), Some((9, "return statement")));
+    }
+
+    #[test]
+    fn test_constants_are_shared_between_functions() {
+        let bytecode = compile(
+            r#"
+answer: function() -> i32 {
+    return 42;
+};
+
+main: function() -> i32 {
+    value: i32 = answer() + 42;
+    return value;
+};
+"#,
+        );
+
+        assert_eq!(bytecode.constants, vec![Value::Int(42)]);
+        assert_eq!(bytecode.main, 1);
+        assert_eq!(
+            bytecode.functions[1].code[..3],
+            [
+                Instruction::Call(0),
+                Instruction::Constant(0),
+                Instruction::BinaryOp(BinaryOperator::Add),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_definition_of_a_function_wins() {
+        let source =
+            |value: i32| format!("main: function() -> i32 {{\n    return {};\n}};\n", value);
+        let mut typed = type_check_program(&parse_program(&source(1)).unwrap()).unwrap();
+        let duplicate = type_check_program(&parse_program(&source(2)).unwrap()).unwrap();
+        typed.functions.extend(duplicate.functions);
+
+        let bytecode = compile_program(&typed).unwrap();
+        assert_eq!(bytecode.main, 0);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::ast::binary_operator_struct::BinaryOperator;
+
+/// One instruction of the stack VM.
+///
+/// Operands are indices into the constant pool, the local slots of the
+/// current frame, the program's functions, `builtins()` or the code of the
+/// current function.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Instruction {
+    /// Pushes the constant at the given index of the constant pool.
+    Constant(usize),
+    /// Pushes a copy of the local variable in the given slot.
+    LoadLocal(usize),
+    /// Pops a value into the local variable in the given slot.
+    StoreLocal(usize),
+    /// Pops the right and then the left operand and pushes the result.
+    BinaryOp(BinaryOperator),
+    /// Pops the given number of strings and pushes their concatenation.
+    Concat(usize),
+    /// Calls the function at the given index with its arguments on top of the stack.
+    Call(usize),
+    /// Restarts the current function with the arguments on top of the stack,
+    /// reusing its frame. Emitted for `return f(...)` inside `f`.
+    TailCall,
+    /// Calls the builtin at the given index of `builtins()`, pushing its result
+    /// unless it returns void.
+    CallBuiltin(usize),
+    /// Discards the top of the stack.
+    Pop,
+    /// Continues at the given instruction.
+    Jump(usize),
+    /// Pops the condition and continues at the given instruction if it is 0.
+    JumpIfFalse(usize),
+    /// Returns the value on top of the stack to the caller.
+    Return,
+    /// Returns from a void function.
+    ReturnVoid,
+}
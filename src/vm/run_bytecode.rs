@@ -0,0 +1,401 @@
+//! Stack VM executing the output of `compile_program`.
+//!
+//! Arguments, locals and temporaries share one value stack, and calls push a
+//! frame onto an explicit frame stack instead of recursing on the host stack,
+//! so deep simple_lang recursion only needs heap memory. Self tail calls reuse
+//! the current frame and do not count towards the call depth limit.
+
+use crate::builtins::builtin_registry::builtins;
+use crate::evaluator::evaluate_function::{evaluate_binary_op, is_truthy};
+use crate::evaluator::evaluation_context::EvaluationContext;
+use crate::evaluator::input_source::StdinSource;
+use crate::evaluator::output_sink::StdoutSink;
+use crate::evaluator::runtime_error::{RuntimeError, RuntimeErrorKind};
+use crate::evaluator::value::Value;
+use crate::vm::bytecode_program_struct::BytecodeProgram;
+use crate::vm::instruction_struct::Instruction;
+
+/// Runs the program starting from its `main` function and returns the exit code.
+/// Program output is written to stdout and input is read from stdin.
+pub fn run_bytecode(program: &BytecodeProgram) -> Result<i32, RuntimeError> {
+    let mut output = StdoutSink;
+    let mut input = StdinSource;
+    run_bytecode_with_context(
+        program,
+        &mut EvaluationContext::new(&mut output, &mut input),
+    )
+}
+
+/// Runs the program like `run_bytecode`, using the given context.
+///
+/// Every executed instruction is one step of the context's fuel, and calls
+/// count towards its `max_call_depth` like in the tree-walker. Runtime errors
+/// carry the same backtrace the tree-walker would report.
+pub fn run_bytecode_with_context(
+    program: &BytecodeProgram,
+    context: &mut EvaluationContext<'_>,
+) -> Result<i32, RuntimeError> {
+    let mut vm = Vm {
+        program,
+        stack: Vec::new(),
+        frames: Vec::new(),
+    };
+
+    let result = vm
+        .call(program.main, context)
+        .and_then(|()| vm.run(context));
+    match result {
+        Ok(Some(Value::Int(code))) => Ok(code),
+        Ok(_) => Err(RuntimeErrorKind::Other(
+            "main function did not return an integer".to_string(),
+        )
+        .into()),
+        Err(error) => Err(vm.unwind(error, context)),
+    }
+}
+
+/// An active function call.
+struct CallFrame {
+    /// Index of the function in the program.
+    function: usize,
+    /// Offset of the next instruction to execute.
+    ip: usize,
+    /// Position of the frame's first local slot on the value stack.
+    base: usize,
+}
+
+struct Vm<'a> {
+    program: &'a BytecodeProgram,
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+}
+
+impl Vm<'_> {
+    /// Executes instructions until the outermost frame returns.
+    fn run(&mut self, context: &mut EvaluationContext<'_>) -> Result<Option<Value>, RuntimeError> {
+        let program = self.program;
+
+        loop {
+            context.step()?;
+
+            let frame = self
+                .frames
+                .last_mut()
+                .expect("a frame is active while running");
+            let function = &program.functions[frame.function];
+            let instruction = &function.code[frame.ip];
+            frame.ip += 1;
+            let base = frame.base;
+
+            match instruction {
+                Instruction::Constant(index) => self.stack.push(program.constants[*index].clone()),
+                Instruction::LoadLocal(slot) => self.stack.push(self.stack[base + slot].clone()),
+                Instruction::StoreLocal(slot) => {
+                    let value = self.pop();
+                    self.stack[base + slot] = value;
+                }
+                Instruction::BinaryOp(op) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(evaluate_binary_op(op, &left, &right)?);
+                }
+                Instruction::Concat(count) => {
+                    let mut text = String::new();
+                    for part in self.stack.split_off(self.stack.len() - count) {
+                        match part {
+                            Value::Str(s) => text.push_str(&s),
+                            _ => {
+                                return Err("Interpolation hole did not evaluate to a string"
+                                    .to_string()
+                                    .into());
+                            }
+                        }
+                    }
+                    self.stack.push(Value::Str(text));
+                }
+                Instruction::Call(index) => self.call(*index, context)?,
+                Instruction::TailCall => {
+                    // Move the arguments into the parameter slots and start over
+                    let args = self
+                        .stack
+                        .split_off(self.stack.len() - function.params.len());
+                    self.stack.truncate(base);
+                    self.stack.extend(args);
+                    self.stack.resize(base + function.locals, Value::Int(0));
+                    self.jump(0);
+                }
+                Instruction::CallBuiltin(index) => {
+                    let builtin = &builtins()[*index];
+                    let args = self
+                        .stack
+                        .split_off(self.stack.len() - builtin.params.len());
+                    if let Some(value) = (builtin.implementation)(args, context)? {
+                        self.stack.push(value);
+                    }
+                }
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::Jump(target) => self.jump(*target),
+                Instruction::JumpIfFalse(target) => {
+                    let condition = self.pop();
+                    if !is_truthy(&condition)? {
+                        self.jump(*target);
+                    }
+                }
+                Instruction::Return => {
+                    let value = self.pop();
+                    if let Some(result) = self.return_from_frame(Some(value), context) {
+                        return Ok(result);
+                    }
+                }
+                Instruction::ReturnVoid => {
+                    if function.returns_value {
+                        return Err(
+                            format!("Function '{}' did not return a value", function.name).into(),
+                        );
+                    }
+                    if let Some(result) = self.return_from_frame(None, context) {
+                        return Ok(result);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Enters the function at `index`, whose arguments are on top of the stack.
+    fn call(
+        &mut self,
+        index: usize,
+        context: &mut EvaluationContext<'_>,
+    ) -> Result<(), RuntimeError> {
+        let function = &self.program.functions[index];
+        if context.call_depth >= context.max_call_depth {
+            return Err(RuntimeErrorKind::StackOverflow {
+                function: function.name.clone(),
+                max_depth: context.max_call_depth,
+            }
+            .into());
+        }
+
+        context.call_depth += 1;
+        let base = self.stack.len() - function.params.len();
+        self.stack.resize(base + function.locals, Value::Int(0));
+        self.frames.push(CallFrame {
+            function: index,
+            ip: 0,
+            base,
+        });
+        Ok(())
+    }
+
+    /// Leaves the current frame and hands its result to the caller.
+    /// Returns the result if the outermost frame returned.
+    fn return_from_frame(
+        &mut self,
+        value: Option<Value>,
+        context: &mut EvaluationContext<'_>,
+    ) -> Option<Option<Value>> {
+        let frame = self.frames.pop().expect("a frame is active while running");
+        self.stack.truncate(frame.base);
+        context.call_depth -= 1;
+
+        if self.frames.is_empty() {
+            return Some(value);
+        }
+        self.stack.extend(value);
+        None
+    }
+
+    fn jump(&mut self, target: usize) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.ip = target;
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack
+            .pop()
+            .expect("compiled code never pops an empty stack")
+    }
+
+    /// Pops every frame, adding each one to the error's backtrace, innermost first.
+    fn unwind(
+        &mut self,
+        mut error: RuntimeError,
+        context: &mut EvaluationContext<'_>,
+    ) -> RuntimeError {
+        while let Some(frame) = self.frames.pop() {
+            let function = &self.program.functions[frame.function];
+            // `ip` already points past the instruction that failed
+            if let Some((line, statement)) = function.statement_at(frame.ip.saturating_sub(1)) {
+                error = error.at_statement(line, || statement.to_string());
+            }
+            let args = function
+                .params
+                .iter()
+                .cloned()
+                .zip(self.stack[frame.base..].iter().cloned())
+                .collect();
+            error = error.in_frame(&function.name, function.file.as_deref(), args);
+            self.stack.truncate(frame.base);
+            context.call_depth -= 1;
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::input_source::ScriptedInput;
+    use crate::evaluator::output_sink::BufferSink;
+    use crate::parser::parse_program::parse_program;
+    use crate::type_checker::type_check_program::type_check_program;
+    use crate::vm::compile_program::compile_program;
+
+    fn compile(source: &str) -> BytecodeProgram {
+        let program = parse_program(source).unwrap();
+        compile_program(&type_check_program(&program).unwrap()).unwrap()
+    }
+
+    const COUNTDOWN: &str = r#"
+countdown: function(n: i32) -> i32 {
+    if (n > 0) {
+        rest: i32 = countdown(n - 1);
+        return rest;
+    };
+    return 0;
+};
+
+main: function() -> i32 {
+    depth: i32 = string_to_int(read_line());
+    print("counting down from {int_to_string(depth)}");
+    return countdown(depth);
+};
+"#;
+
+    fn run_countdown(depth: &str, max_call_depth: usize) -> (Result<i32, RuntimeError>, usize) {
+        let program = compile(COUNTDOWN);
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::new([depth]);
+        let mut context =
+            EvaluationContext::new(&mut output, &mut input).with_max_call_depth(max_call_depth);
+        let result = run_bytecode_with_context(&program, &mut context);
+        (result, context.call_depth)
+    }
+
+    #[test]
+    fn test_runs_programs_with_input_and_output() {
+        let program = compile(
+            r#"
+greet: function(name: string) -> void {
+    if (name == "") {
+        return;
+    };
+    print("Hello, {name}!");
+};
+
+main: function() -> i32 {
+    greet(read_line());
+    greet("");
+    return string_to_int(read_line()) * 2;
+};
+"#,
+        );
+
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::new(["Ada", "21"]);
+        let exit_code = run_bytecode_with_context(
+            &program,
+            &mut EvaluationContext::new(&mut output, &mut input),
+        );
+        assert_eq!(exit_code, Ok(42));
+        assert_eq!(output.contents(), "Hello, Ada!\n");
+    }
+
+    #[test]
+    fn test_recursion_does_not_use_the_host_stack() {
+        // Far deeper than the stack of the test thread could hold if every
+        // simple_lang call nested a Rust call
+        assert_eq!(run_countdown("100000", 200_000), (Ok(0), 0));
+
+        let (result, call_depth) = run_countdown("48", 49);
+        assert_eq!(call_depth, 0);
+        assert_eq!(
+            result.map_err(|error| error.kind),
+            Err(RuntimeErrorKind::StackOverflow {
+                function: "countdown".to_string(),
+                max_depth: 49,
+            })
+        );
+    }
+
+    #[test]
+    fn test_runtime_error_backtrace_matches_the_tree_walker() {
+        let program = compile(
+            r#"
+divide: function(a: i32, b: i32) -> i32 {
+    return a / b;
+};
+
+main: function() -> i32 {
+    zero: i32 = 0;
+    if (zero < 1) {
+        result: i32 = divide(10, zero);
+    };
+    return 0;
+};
+"#,
+        );
+
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let error = run_bytecode_with_context(
+            &program,
+            &mut EvaluationContext::new(&mut output, &mut input),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Division by zero\n  at divide(a = 10, b = 0), line 3, return statement\n  at main(), line 9, declaration of 'result'"
+        );
+    }
+
+    #[test]
+    fn test_fuel_counts_instructions() {
+        let program = compile(COUNTDOWN);
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::new(["10"]);
+        let mut context = EvaluationContext::new(&mut output, &mut input).with_fuel(20);
+        assert_eq!(
+            run_bytecode_with_context(&program, &mut context).map_err(|error| error.kind),
+            Err(RuntimeErrorKind::FuelExhausted { steps: 20 })
+        );
+    }
+
+    #[test]
+    fn test_self_tail_calls_reuse_the_frame() {
+        let program = compile(
+            r#"
+count_to: function(n: i32, total: i32) -> i32 {
+    if (n == 0) {
+        return total;
+    };
+    next: i32 = n - 1;
+    return count_to(next, total + 1);
+};
+
+main: function() -> i32 {
+    return count_to(1000000, 0) - 1000000;
+};
+"#,
+        );
+        assert!(program.functions[0].code.contains(&Instruction::TailCall));
+
+        let mut output = BufferSink::default();
+        let mut input = ScriptedInput::default();
+        let mut context = EvaluationContext::new(&mut output, &mut input).with_max_call_depth(2);
+        assert_eq!(run_bytecode_with_context(&program, &mut context), Ok(0));
+    }
+}